@@ -1,20 +1,114 @@
-use clap::{Parser, Subcommand};
+use clap::{Parser, Subcommand, ValueEnum};
+use std::path::PathBuf;
+
+use crate::exchange::ExchangeFormat;
+
+use crate::storage::StoreSpec;
+
+pub mod note;
+pub use note::*;
+
+pub mod report;
+pub use report::*;
 
 pub mod tasks;
 pub use tasks::*;
 
+pub mod timer;
+pub use timer::*;
+
 #[derive(Parser)]
 #[command(version, about, long_about = None)]
 pub struct Cli {
   #[command(subcommand)]
   pub command: Command,
+  /// Where tasks are kept, e.g. `md:tasks.md`, `json:tasks.json`, `sqlite:tasks.db`,
+  /// `todo:todo.txt` or `dir:notes` for every markdown file under a directory
+  #[arg(long, global = true)]
+  pub store: Option<StoreSpec>,
 }
 
 #[derive(Subcommand)]
 pub enum Command {
   #[command(alias = "t")]
   Tasks(tasks::TaskArgs),
-  // Timer(TimerArgs),
-  // Note(NoteArgs),
-  Status,
+  Timer(timer::TimerArgs),
+  /// Links between notes and tasks
+  Note(note::NoteArgs),
+  /// The task being timed and for how long on one line, e.g. for a shell prompt,
+  /// or time logged against the estimate of a task
+  Status {
+    /// The task's description, or a part of it only one task has
+    task: Option<String>,
+    /// Time logged against the estimate of every open task
+    #[arg(long, conflicts_with = "task")]
+    all: bool,
+  },
+  /// Search tasks, the notes kept with them and the notes in the journal
+  Search {
+    /// Words that all have to be found, or a regex with `--regex`
+    query: String,
+    #[arg(short, long)]
+    regex: bool,
+    #[arg(short, long)]
+    ignore_case: bool,
+    /// Which tasks to search. Notes outside tasks are only searched with `all`
+    #[arg(short, long, value_enum, default_value_t = ListShow::All)]
+    show: ListShow,
+    #[arg(short, long, value_enum, default_value_t = SearchFormat::Plain)]
+    format: SearchFormat,
+  },
+  /// Open today's note, created from a template with yesterday's open tasks rolled over
+  Journal {
+    /// Which day's note, e.g. `yesterday` or `2026-10-17`. Defaults to today
+    #[arg(long)]
+    date: Option<String>,
+    /// Add the day's completed tasks and logged time to the end of the note
+    #[arg(long)]
+    summary: bool,
+  },
+  /// Summarise tracked time
+  Report(report::ReportArgs),
+  /// Copy all tasks from one store to another, e.g. `--from md --to json`
+  Migrate {
+    #[arg(long)]
+    from: StoreSpec,
+    #[arg(long)]
+    to: StoreSpec,
+  },
+  /// Import tasks exported from another task manager
+  Import {
+    #[arg(short, long, value_enum)]
+    format: ImportFormat,
+    file: PathBuf,
+  },
+  /// Export tasks for another task manager, to stdout unless `--output` is given
+  Export {
+    #[arg(short, long, value_enum)]
+    format: ImportFormat,
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+  },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum SearchFormat {
+  Plain,
+  Json,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum ImportFormat {
+  Taskwarrior,
+  /// iCalendar, as tasks (VTODO)
+  Ics,
+}
+
+impl From<ImportFormat> for ExchangeFormat {
+  fn from(format: ImportFormat) -> ExchangeFormat {
+    match format {
+      ImportFormat::Taskwarrior => ExchangeFormat::Taskwarrior,
+      ImportFormat::Ics => ExchangeFormat::Ical,
+    }
+  }
 }
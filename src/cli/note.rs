@@ -0,0 +1,18 @@
+use clap::{Args, Subcommand};
+
+#[derive(Args)]
+pub struct NoteArgs {
+  #[command(subcommand)]
+  pub command: NoteCommand,
+}
+
+#[derive(Subcommand)]
+pub enum NoteCommand {
+  /// List every task and note that links to a note or task with `[[name]]`
+  Backlinks {
+    /// The note's file name without `.md`, or a task's description or title
+    note: String,
+  },
+  /// List the links that don't point to any note or task
+  Check,
+}
@@ -0,0 +1,52 @@
+use clap::{Args, Subcommand, ValueEnum};
+
+use crate::tasks::OutputFormat;
+use crate::timer::GroupBy;
+
+#[derive(Args)]
+pub struct ReportArgs {
+  #[command(subcommand)]
+  pub command: ReportCommand,
+}
+
+#[derive(Subcommand)]
+pub enum ReportCommand {
+  /// Tracked time with totals, e.g. for a timesheet
+  Time {
+    /// First day to include, e.g. `2026-10-01` or `today-7d`. Defaults to the start of the month
+    #[arg(long)]
+    from: Option<String>,
+    /// Last day to include. Defaults to today
+    #[arg(long)]
+    to: Option<String>,
+    #[arg(short, long, value_enum, default_value_t = ReportGroupBy::Task)]
+    group_by: ReportGroupBy,
+    #[arg(short, long, value_enum, default_value_t = OutputFormat::Plain)]
+    format: OutputFormat,
+  },
+  /// Tasks that ran over their `~2h` estimate, and how accurate estimates are by tag
+  Estimates {
+    #[arg(short, long, value_enum, default_value_t = OutputFormat::Plain)]
+    format: OutputFormat,
+  },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum ReportGroupBy {
+  Task,
+  Tag,
+  /// The heading a task is under in markdown
+  Section,
+  Day,
+}
+
+impl From<ReportGroupBy> for GroupBy {
+  fn from(group_by: ReportGroupBy) -> GroupBy {
+    match group_by {
+      ReportGroupBy::Task => GroupBy::Task,
+      ReportGroupBy::Tag => GroupBy::Tag,
+      ReportGroupBy::Section => GroupBy::Section,
+      ReportGroupBy::Day => GroupBy::Day,
+    }
+  }
+}
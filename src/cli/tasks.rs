@@ -1,4 +1,6 @@
-use clap::{Args, Subcommand};
+use clap::{Args, Subcommand, ValueEnum};
+
+use crate::tasks::{GetTasksFilterOption, OutputFormat};
 
 #[derive(Args)]
 pub struct TaskArgs {
@@ -8,10 +10,54 @@ pub struct TaskArgs {
 
 #[derive(Subcommand)]
 pub enum TaskCommand {
-  /// Add a new task
+  /// Add a new task, e.g. "Call Bob tomorrow 3pm p1 #sales @phone every friday"
   #[command(alias = "a")]
-  Add { description: String },
+  Add {
+    description: String,
+    /// Show how the description was understood without adding the task
+    #[arg(long)]
+    dry_run: bool,
+  },
+  /// Change a task's description, or open it with its notes in $EDITOR
+  #[command(alias = "e")]
+  Edit {
+    /// The task's description, or a part of it only one task has
+    task: String,
+    /// The new description
+    #[arg(required_unless_present = "editor", conflicts_with = "editor")]
+    description: Option<String>,
+    /// Open the task and its notes in $VISUAL or $EDITOR
+    #[arg(long)]
+    editor: bool,
+  },
   /// List tasks
   #[command(alias = "l")]
-  List,
+  List {
+    /// Which tasks to list
+    #[arg(short, long, value_enum, default_value_t = ListShow::All)]
+    show: ListShow,
+    /// Filter expression or saved query name, e.g. `status:open and tag:backend`
+    #[arg(long, conflicts_with = "show")]
+    filter: Option<String>,
+    /// Print the list instead of opening the interactive view
+    #[arg(short, long, value_enum)]
+    format: Option<OutputFormat>,
+  },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum ListShow {
+  All,
+  Completed,
+  Incomplete,
+}
+
+impl From<ListShow> for GetTasksFilterOption {
+  fn from(show: ListShow) -> GetTasksFilterOption {
+    match show {
+      ListShow::All => GetTasksFilterOption::All,
+      ListShow::Completed => GetTasksFilterOption::Completed,
+      ListShow::Incomplete => GetTasksFilterOption::Incomplete,
+    }
+  }
 }
@@ -0,0 +1,54 @@
+use clap::{Args, Subcommand};
+
+#[derive(Args)]
+pub struct TimerArgs {
+  #[command(subcommand)]
+  pub command: TimerCommand,
+}
+
+#[derive(Subcommand)]
+pub enum TimerCommand {
+  /// Work on a task in pomodoros, 25 minutes of work and a 5 minute break by default
+  Pomodoro {
+    /// The task's description, or a part of it only one task has
+    task: String,
+  },
+  /// Start timing a task, stopping whatever else was being timed
+  Start {
+    /// The task's description, or a part of it only one task has
+    task: String,
+  },
+  /// Stop the running timer
+  Stop,
+  /// Add time spent on a task after the fact, e.g. `timer log spec 1h30m --at "yesterday 14:00"`,
+  /// or list the task's time entries
+  Log {
+    /// The task's description, or a part of it only one task has
+    task: String,
+    /// How long, e.g. 1h30m, 45m or 1:30
+    duration: Option<String>,
+    /// When it started, e.g. "yesterday 14:00" or "9:30am"; it ended now by default
+    #[arg(long, requires = "duration")]
+    at: Option<String>,
+  },
+  /// Move a time entry or change its length
+  Edit {
+    /// The task's description, or a part of it only one task has
+    task: String,
+    /// The entry's number in `timer log <task>`
+    entry: usize,
+    /// When it started, keeping its length
+    #[arg(long, required_unless_present = "duration")]
+    at: Option<String>,
+    /// How long it was, e.g. 1h30m
+    #[arg(long)]
+    duration: Option<String>,
+  },
+  /// Remove a time entry
+  Rm {
+    /// The task's description, or a part of it only one task has
+    task: String,
+    /// The entry's number in `timer log <task>`
+    entry: usize,
+  },
+}
@@ -0,0 +1,92 @@
+use anyhow::{Result, anyhow};
+use serde::Deserialize;
+use std::{collections::HashMap, fs, io::ErrorKind};
+
+use crate::tasks::Filter;
+use crate::timer::report::Rounding;
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct Config {
+  /// Saved filter expressions, usable by name wherever a filter is accepted
+  pub queries: HashMap<String, String>,
+  /// Keep a `.bak` copy of the tasks file from before each save
+  pub backup: bool,
+  /// Where tasks are kept, e.g. `json:tasks.json`. Defaults to `md:tasks.md`
+  pub store: Option<String>,
+  /// How tracked time is rounded in reports
+  pub report: ReportConfig,
+  pub pomodoro: PomodoroConfig,
+  pub journal: JournalConfig,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct ReportConfig {
+  /// Minutes each time entry is rounded to, e.g. 15. Rounded to the minute when 0
+  pub round_to: i64,
+  pub rounding: Rounding,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct JournalConfig {
+  /// Where the daily notes go. Defaults to `journal`, inside the directory
+  /// of a `dir:` store so their checklists are read as tasks
+  pub dir: Option<String>,
+  /// A markdown file to create each note from, with `{{date}}`, `{{weekday}}`
+  /// and a `{{tasks}}` line for the tasks rolled over from the last note
+  pub template: Option<String>,
+}
+
+/// Lengths, in minutes, of the intervals of `timer pomodoro`
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct PomodoroConfig {
+  pub work: u32,
+  pub short_break: u32,
+  pub long_break: u32,
+  /// How many pomodoros come before a long break
+  pub long_break_after: u32,
+}
+
+impl Default for PomodoroConfig {
+  fn default() -> PomodoroConfig {
+    PomodoroConfig {
+      work: 25,
+      short_break: 5,
+      long_break: 15,
+      long_break_after: 4,
+    }
+  }
+}
+
+impl Config {
+  pub fn load(path: &str) -> Result<Config> {
+    match fs::read_to_string(path) {
+      Ok(contents) => {
+        let config: Config = toml::from_str(&contents)
+          .map_err(|err| anyhow!("Invalid config in {}: {}", path, err))?;
+        // the timer would move through intervals of no length without end
+        let pomodoro = &config.pomodoro;
+        if [pomodoro.work, pomodoro.short_break, pomodoro.long_break].contains(&0) {
+          return Err(anyhow!(
+            "Invalid config in {}: pomodoro intervals must be at least a minute",
+            path
+          ));
+        }
+        Ok(config)
+      }
+      Err(err) if err.kind() == ErrorKind::NotFound => Ok(Config::default()),
+      Err(err) => Err(err.into()),
+    }
+  }
+
+  /// Parses `input` as a filter, or as the name of a saved query
+  pub fn resolve_filter(&self, input: &str) -> Result<Filter> {
+    match self.queries.get(input.trim()) {
+      Some(query) => Filter::parse(query),
+      None => Filter::parse(input),
+    }
+  }
+}
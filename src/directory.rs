@@ -0,0 +1,376 @@
+use anyhow::{Result, anyhow};
+use log::{debug, info};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+use crate::markdown::{self, ConflictResolver};
+use crate::storage;
+use crate::tasks::{
+  GetTasksFilterOption, TaskChange, TaskList, TaskListPersist, TaskStatus, TaskUpdateAction,
+};
+use crate::timer::{TaskTime, TimeEntry};
+
+/// Where tasks added through taskmaster go, relative to the directory
+const INBOX: &str = "tasks.md";
+
+/// The file, as an index into `Directory::files`, and line a task was read from
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Source {
+  pub file: usize,
+  pub line: usize,
+}
+
+/// Every markdown file under a directory, read as one list of tasks
+pub struct Directory<'a> {
+  pub path: &'a str,
+  files: Vec<(markdown::File<'static>, TaskList)>,
+  sources: HashMap<String, Source>,
+  /// Tasks the user was already told are in two files, so a reload doesn't
+  /// tell them again
+  duplicates: HashSet<String>,
+  backup: bool,
+  resolve_conflict: Option<ConflictResolver>,
+  lock: Option<fs::File>,
+}
+
+impl<'a> Directory<'a> {
+  pub fn from(path: &'a str) -> Directory<'a> {
+    Directory {
+      path,
+      files: Vec::new(),
+      sources: HashMap::new(),
+      duplicates: HashSet::new(),
+      backup: false,
+      resolve_conflict: None,
+      lock: None,
+    }
+  }
+
+  /// Keep the previous version of each file as `<path>.bak` on every save
+  pub fn with_backup(mut self, backup: bool) -> Directory<'a> {
+    self.backup = backup;
+    self
+  }
+
+  /// Called for each task that was changed both here and in its file
+  /// since it was loaded
+  pub fn with_conflict_resolver(mut self, resolver: ConflictResolver) -> Directory<'a> {
+    self.resolve_conflict = Some(resolver);
+    self
+  }
+
+  /// The file and line a task was loaded from
+  pub fn source(&self, description: &str) -> Option<(&str, usize)> {
+    let source = self.sources.get(description)?;
+    Some((&self.files[source.file].0.path, source.line))
+  }
+
+  /// The index of the file a task is in, loading the directory if needed
+  fn source_file(&mut self, description: &str) -> Result<usize> {
+    if !self.sources.contains_key(description) {
+      self.load_tasklist()?;
+    }
+    self
+      .sources
+      .get(description)
+      .map(|source| source.file)
+      .ok_or_else(|| anyhow!("No task \"{}\" in {}", description, self.path))
+  }
+
+  fn open_file(&self, path: String) -> markdown::File<'static> {
+    let file = markdown::File::from(path).with_backup(self.backup);
+    match self.resolve_conflict {
+      Some(resolver) => file.with_conflict_resolver(resolver),
+      None => file,
+    }
+  }
+
+  /// The file new tasks are added to, created on first use
+  fn inbox(&mut self) -> Result<usize> {
+    let path = Path::new(self.path).join(INBOX);
+    if let Some(i) = self
+      .files
+      .iter()
+      .position(|(file, _)| Path::new(&*file.path) == path)
+    {
+      return Ok(i);
+    }
+
+    fs::create_dir_all(self.path)?;
+    let mut file = self.open_file(path.to_string_lossy().into_owned());
+    let tasklist = file.load_tasklist()?;
+    self.files.push((file, tasklist));
+
+    Ok(self.files.len() - 1)
+  }
+
+  fn apply_change(&mut self, change: TaskChange) -> Result<()> {
+    debug!("applying {:?}", change);
+
+    let original = match change {
+      TaskChange::Added(task) => {
+        let inbox = self.inbox()?;
+        let tasklist = &mut self.files[inbox].1;
+        tasklist.add_task(task.description.clone())?;
+        if task.status != TaskStatus::Todo {
+          tasklist.update_task(TaskUpdateAction::SetStatus(task.status), &task.description);
+        }
+        return Ok(());
+      }
+      TaskChange::Updated { ref original, .. } | TaskChange::Deleted(ref original) => {
+        original.clone()
+      }
+    };
+
+    let Some(source) = self.sources.get(&original).copied() else {
+      return Ok(());
+    };
+    let (file, tasklist) = &mut self.files[source.file];
+    debug!("changing {}:{}", file.path, source.line);
+    let Some(current) = tasklist.get_task(&original) else {
+      return Ok(());
+    };
+
+    match change {
+      TaskChange::Updated { task, .. } => {
+        if current.description != task.description {
+          tasklist.update_task(TaskUpdateAction::Edit(&task.description), &original);
+        }
+        if current.status != task.status {
+          tasklist.update_task(TaskUpdateAction::SetStatus(task.status), &task.description);
+        }
+      }
+      TaskChange::Deleted(_) => {
+        tasklist.update_task(TaskUpdateAction::Delete, &original);
+      }
+      TaskChange::Added(_) => {}
+    }
+
+    Ok(())
+  }
+}
+
+/// Finds every markdown file under `dir`, skipping hidden files and
+/// directories such as `.git` or `.obsidian`
+pub fn scan(dir: &Path, paths: &mut Vec<PathBuf>) -> Result<()> {
+  let entries = match fs::read_dir(dir) {
+    Ok(entries) => entries,
+    // created when the first task is added
+    Err(err) if err.kind() == ErrorKind::NotFound => return Ok(()),
+    Err(err) => return Err(err.into()),
+  };
+
+  let mut entries = entries
+    .map(|entry| entry.map(|entry| entry.path()))
+    .collect::<std::io::Result<Vec<PathBuf>>>()?;
+  entries.sort();
+
+  for path in entries {
+    let is_hidden = path
+      .file_name()
+      .is_some_and(|name| name.to_string_lossy().starts_with('.'));
+    if is_hidden {
+      continue;
+    }
+
+    if path.is_dir() {
+      scan(&path, paths)?;
+    } else if path.extension().is_some_and(|ext| ext == "md") {
+      paths.push(path);
+    }
+  }
+
+  Ok(())
+}
+
+impl<'a> TaskListPersist for Directory<'a> {
+  fn lock(&mut self) -> Result<()> {
+    self.lock = Some(storage::lock(self.path)?);
+    Ok(())
+  }
+
+  fn unlock(&mut self) {
+    self.lock = None;
+  }
+
+  fn watch_path(&self) -> Option<PathBuf> {
+    Some(PathBuf::from(self.path))
+  }
+
+  fn load_tasklist(&mut self) -> Result<TaskList> {
+    info!("loading tasks from {}", self.path);
+    self.files.clear();
+    self.sources.clear();
+
+    let mut paths = Vec::new();
+    scan(Path::new(self.path), &mut paths)?;
+
+    let mut tasks = Vec::new();
+    for path in paths {
+      let mut file = self.open_file(path.to_string_lossy().into_owned());
+      let tasklist = file.load_tasklist()?;
+      let lines = file.task_lines();
+
+      for task in tasklist.get_tasks(&GetTasksFilterOption::All) {
+        let first = self
+          .source(&task.description)
+          .map(|(path, line)| format!("{}:{}", path, line));
+        if let Some(first) = first {
+          info!(
+            "ignoring \"{}\" in {}, already in {}",
+            task.description, file.path, first
+          );
+          // a task is known by its description, so only the first can be listed
+          if self.duplicates.insert(task.description.clone()) {
+            eprintln!(
+              "\"{}\" is in both {} and {}:{}, only the first is listed",
+              task.description,
+              first,
+              file.path,
+              lines.get(&task.description).copied().unwrap_or_default()
+            );
+          }
+          continue;
+        }
+
+        let source = Source {
+          file: self.files.len(),
+          line: lines.get(&task.description).copied().unwrap_or_default(),
+        };
+        self.sources.insert(task.description.clone(), source);
+        tasks.push(task);
+      }
+      self.files.push((file, tasklist));
+    }
+
+    Ok(TaskList::from(tasks))
+  }
+
+  fn save_tasklist(&mut self, tasklist: &mut TaskList) -> Result<()> {
+    for change in tasklist.changes() {
+      self.apply_change(change)?;
+    }
+
+    for (file, tasklist) in self.files.iter_mut() {
+      if tasklist.has_changes() {
+        info!("saving {}", file.path);
+        file.save_tasklist(tasklist)?;
+      }
+    }
+
+    Ok(())
+  }
+
+  /// Tasks outside of any heading are in the section named after their file
+  fn load_time_entries(&mut self) -> Result<Vec<TaskTime>> {
+    let mut paths = Vec::new();
+    scan(Path::new(self.path), &mut paths)?;
+
+    let mut entries = Vec::new();
+    for path in paths {
+      let name = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned());
+      let mut file = self.open_file(path.to_string_lossy().into_owned());
+      for mut time in file.load_time_entries()? {
+        time.section = time.section.or_else(|| name.clone());
+        entries.push(time);
+      }
+    }
+
+    Ok(entries)
+  }
+
+  fn save_time_entries(&mut self, description: &str, entries: &[TimeEntry]) -> Result<()> {
+    let file = self.source_file(description)?;
+    self.files[file].0.save_time_entries(description, entries)
+  }
+
+  fn load_notes(&mut self, description: &str) -> Result<Vec<String>> {
+    let file = self.source_file(description)?;
+    self.files[file].0.load_notes(description)
+  }
+
+  fn save_notes(&mut self, description: &str, notes: &[String]) -> Result<()> {
+    let file = self.source_file(description)?;
+    self.files[file].0.save_notes(description, notes)
+  }
+
+  fn load_all_notes(&mut self) -> Result<HashMap<String, Vec<String>>> {
+    if self.files.is_empty() {
+      self.load_tasklist()?;
+    }
+
+    let mut all = HashMap::new();
+    for (i, (file, _)) in self.files.iter_mut().enumerate() {
+      for (description, notes) in file.load_all_notes()? {
+        // only those of the task that's listed, not of one it hides
+        if self
+          .sources
+          .get(&description)
+          .is_some_and(|source| source.file == i)
+        {
+          all.insert(description, notes);
+        }
+      }
+    }
+
+    Ok(all)
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn test_changes_go_back_to_their_file() {
+    let dir = std::env::temp_dir().join(format!("taskmaster-dir-{}", std::process::id()));
+    fs::create_dir_all(dir.join("projects/.hidden")).unwrap();
+    fs::write(dir.join("meeting.md"), "# Monday\n\n- [ ] send notes\n").unwrap();
+    fs::write(
+      dir.join("projects/api.md"),
+      "# API\n- [ ] add auth\n- [ ] send notes\n- [x] write spec\n",
+    )
+    .unwrap();
+    fs::write(dir.join("projects/.hidden/skip.md"), "- [ ] hidden\n").unwrap();
+    fs::write(dir.join("projects/readme.txt"), "- [ ] not markdown\n").unwrap();
+    let path = dir.to_string_lossy().to_string();
+
+    let mut store = Directory::from(&path);
+    let mut tasklist = store.load_tasklist().unwrap();
+    let descriptions: Vec<String> = tasklist
+      .get_tasks(&GetTasksFilterOption::All)
+      .into_iter()
+      .map(|task| task.description)
+      .collect();
+    assert_eq!(vec!["send notes", "add auth", "write spec"], descriptions);
+
+    let (file, line) = store.source("write spec").unwrap();
+    assert!(file.ends_with("api.md"));
+    assert_eq!(4, line);
+
+    tasklist.update_task(TaskUpdateAction::Toggle, "send notes");
+    tasklist.update_task(TaskUpdateAction::Edit("add oauth"), "add auth");
+    tasklist.update_task(TaskUpdateAction::Delete, "write spec");
+    tasklist.add_task(String::from("new idea")).unwrap();
+    store.save_tasklist(&mut tasklist).unwrap();
+
+    assert_eq!(
+      "# Monday\n\n- [x] send notes\n",
+      fs::read_to_string(dir.join("meeting.md")).unwrap()
+    );
+    assert_eq!(
+      "# API\n- [ ] add oauth\n- [ ] send notes\n",
+      fs::read_to_string(dir.join("projects/api.md")).unwrap()
+    );
+    assert_eq!(
+      "- [ ] new idea\n",
+      fs::read_to_string(dir.join(INBOX)).unwrap()
+    );
+
+    fs::remove_dir_all(&dir).unwrap();
+  }
+}
@@ -0,0 +1,28 @@
+use anyhow::{Result, anyhow};
+use std::{env, path::Path, process::Command};
+
+/// Opens `path` in `$VISUAL` or `$EDITOR`, which may include arguments such
+/// as `code --wait` or a quoted path, and waits for it to be closed. Returns
+/// false when neither is set.
+pub fn edit(path: &Path) -> Result<bool> {
+  let Some(editor) = ["VISUAL", "EDITOR"]
+    .iter()
+    .find_map(|name| env::var(name).ok().filter(|value| !value.trim().is_empty()))
+  else {
+    return Ok(false);
+  };
+
+  // run by the shell as git does, so it's read the way the user wrote it
+  let status = Command::new("sh")
+    .arg("-c")
+    .arg(format!("{} \"$@\"", editor))
+    .arg(&editor)
+    .arg(path)
+    .status()
+    .map_err(|err| anyhow!("Couldn't run {}: {}", editor, err))?;
+  if !status.success() {
+    return Err(anyhow!("{} exited with {}", editor, status));
+  }
+
+  Ok(true)
+}
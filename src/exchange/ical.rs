@@ -0,0 +1,461 @@
+use anyhow::{Result, anyhow};
+use chrono::{Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+use std::io::Write;
+
+use super::{ImportReport, ImportedTask, task_uid};
+use crate::tasks::{Task, TaskMeta, TaskStatus};
+
+const DATE_FORMAT: &str = "%Y%m%d";
+const DATE_TIME_FORMAT: &str = "%Y%m%dT%H%M%S";
+
+/// Properties that are mapped onto a task, or that only matter to the
+/// calendar and so aren't worth reporting when they're dropped
+const KNOWN_PROPERTIES: &[&str] = &[
+  "UID",
+  "SUMMARY",
+  "STATUS",
+  "DUE",
+  "PRIORITY",
+  "CATEGORIES",
+  "COMPLETED",
+  "CREATED",
+  "DTSTAMP",
+  "LAST-MODIFIED",
+  "SEQUENCE",
+  "PERCENT-COMPLETE",
+];
+
+/// A content line, e.g. `DUE;VALUE=DATE:20261019`
+#[derive(Debug, PartialEq)]
+struct Property {
+  name: String,
+  params: Vec<(String, String)>,
+  value: String,
+}
+
+impl Property {
+  fn parse(line: &str) -> Option<Property> {
+    // the value starts at the first colon that isn't in a quoted parameter
+    let mut in_quotes = false;
+    let split = line.char_indices().find_map(|(i, c)| match c {
+      '"' => {
+        in_quotes = !in_quotes;
+        None
+      }
+      ':' if !in_quotes => Some(i),
+      _ => None,
+    })?;
+
+    let mut parts = line[..split].split(';');
+    let name = parts.next()?.to_ascii_uppercase();
+    let params = parts
+      .filter_map(|param| param.split_once('='))
+      .map(|(key, value)| {
+        (
+          key.to_ascii_uppercase(),
+          value.trim_matches('"').to_string(),
+        )
+      })
+      .collect();
+
+    Some(Property {
+      name,
+      params,
+      value: line[split + 1..].to_string(),
+    })
+  }
+
+  fn param(&self, key: &str) -> Option<&str> {
+    self
+      .params
+      .iter()
+      .find(|(k, _)| k == key)
+      .map(|(_, value)| value.as_str())
+  }
+
+  /// Dates and times, in local time. `Z` marks UTC and anything else is
+  /// taken as local, even with a `TZID`.
+  fn date_time(&self) -> Option<(NaiveDate, Option<NaiveTime>)> {
+    if let Ok(date) = NaiveDate::parse_from_str(&self.value, DATE_FORMAT) {
+      return Some((date, None));
+    }
+
+    let date_time = match self.value.strip_suffix('Z') {
+      Some(value) => {
+        let date_time = NaiveDateTime::parse_from_str(value, DATE_TIME_FORMAT).ok()?;
+        Utc
+          .from_utc_datetime(&date_time)
+          .with_timezone(&Local)
+          .naive_local()
+      }
+      None => NaiveDateTime::parse_from_str(&self.value, DATE_TIME_FORMAT).ok()?,
+    };
+    Some((date_time.date(), Some(date_time.time())))
+  }
+}
+
+fn escape(text: &str) -> String {
+  text
+    .replace('\\', "\\\\")
+    .replace(';', "\\;")
+    .replace(',', "\\,")
+    .replace('\n', "\\n")
+}
+
+fn unescape(text: &str) -> String {
+  let mut unescaped = String::new();
+  let mut chars = text.chars();
+  while let Some(c) = chars.next() {
+    if c != '\\' {
+      unescaped.push(c);
+      continue;
+    }
+    match chars.next() {
+      Some('n') | Some('N') => unescaped.push('\n'),
+      Some(c) => unescaped.push(c),
+      None => {}
+    }
+  }
+  unescaped
+}
+
+/// Splits on commas that aren't escaped, as used by `CATEGORIES`
+fn split_list(text: &str) -> Vec<String> {
+  let mut items = vec![String::new()];
+  let mut chars = text.chars();
+  while let Some(c) = chars.next() {
+    match c {
+      '\\' => {
+        items.last_mut().unwrap().push(c);
+        if let Some(c) = chars.next() {
+          items.last_mut().unwrap().push(c);
+        }
+      }
+      ',' => items.push(String::new()),
+      c => items.last_mut().unwrap().push(c),
+    }
+  }
+  items.iter().map(|item| unescape(item)).collect()
+}
+
+/// Writes a content line, folded so no line is longer than 75 bytes
+fn write_line<W: Write>(out: &mut W, line: &str) -> Result<()> {
+  let mut start = 0;
+  let mut limit = 75;
+  while line.len() - start > limit {
+    let mut end = start + limit;
+    while !line.is_char_boundary(end) {
+      end -= 1;
+    }
+    write!(out, "{}\r\n ", &line[start..end])?;
+    start = end;
+    // the space at the start of continuation lines counts towards the limit
+    limit = 74;
+  }
+  write!(out, "{}\r\n", &line[start..])?;
+
+  Ok(())
+}
+
+fn utc(date: NaiveDate, time: Option<NaiveTime>) -> Option<String> {
+  let date_time = Local
+    .from_local_datetime(&date.and_time(time.unwrap_or_default()))
+    .earliest()?;
+  Some(format!(
+    "{}Z",
+    date_time.with_timezone(&Utc).format(DATE_TIME_FORMAT)
+  ))
+}
+
+/// Writes a calendar with a `VTODO` for every task
+pub fn write<W: Write>(out: &mut W, tasks: &[Task]) -> Result<()> {
+  let stamp = format!("{}Z", Utc::now().format(DATE_TIME_FORMAT));
+
+  write_line(out, "BEGIN:VCALENDAR")?;
+  write_line(out, "VERSION:2.0")?;
+  write_line(out, "PRODID:-//taskmaster//taskmaster//EN")?;
+
+  for task in tasks {
+    let meta = TaskMeta::parse(&task.description);
+    // there are no properties for these so they stay in the summary
+    let summary = TaskMeta {
+      title: meta.title.clone(),
+      context: meta.context.clone(),
+      recurrence: meta.recurrence.clone(),
+      emoji: meta.emoji,
+      ..TaskMeta::default()
+    }
+    .to_description();
+
+    write_line(out, "BEGIN:VTODO")?;
+    write_line(out, &format!("UID:{}", task_uid(&task.description)))?;
+    write_line(out, &format!("DTSTAMP:{}", stamp))?;
+    write_line(out, &format!("SUMMARY:{}", escape(&summary)))?;
+    let status = match task.status {
+      TaskStatus::Done => "COMPLETED",
+      TaskStatus::Cancelled => "CANCELLED",
+      TaskStatus::InProgress => "IN-PROCESS",
+      _ => "NEEDS-ACTION",
+    };
+    write_line(out, &format!("STATUS:{}", status))?;
+
+    match (meta.due, meta.due_time) {
+      (Some(due), None) => {
+        let due = due.format(DATE_FORMAT);
+        write_line(out, &format!("DUE;VALUE=DATE:{}", due))?
+      }
+      // floating time, so it shows at the same hour wherever the calendar is
+      (Some(due), Some(time)) => write_line(
+        out,
+        &format!("DUE:{}", due.and_time(time).format(DATE_TIME_FORMAT)),
+      )?,
+      _ => {}
+    }
+    if let Some(priority) = meta.priority {
+      // 1 is the highest priority and 9 the lowest
+      write_line(out, &format!("PRIORITY:{}", priority * 2 - 1))?;
+    }
+    if !meta.tags.is_empty() {
+      let categories: Vec<String> = meta.tags.iter().map(|tag| escape(tag)).collect();
+      write_line(out, &format!("CATEGORIES:{}", categories.join(",")))?;
+    }
+    if let Some(created) = meta.created.and_then(|created| utc(created, None)) {
+      write_line(out, &format!("CREATED:{}", created))?;
+    }
+    if let Some(completed) = meta.done.and_then(|done| utc(done, None)) {
+      write_line(out, &format!("COMPLETED:{}", completed))?;
+    }
+    write_line(out, "END:VTODO")?;
+  }
+
+  write_line(out, "END:VCALENDAR")?;
+
+  Ok(())
+}
+
+/// Reads every `VTODO` in a calendar, ignoring any other components
+pub fn parse(input: &str, report: &mut ImportReport) -> Result<Vec<ImportedTask>> {
+  // unfold lines continued with a leading space or tab
+  let mut lines: Vec<String> = Vec::new();
+  for line in input.lines() {
+    match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+      (Some(continued), Some(last)) => last.push_str(continued),
+      _ => lines.push(line.to_string()),
+    }
+  }
+
+  let mut tasks = Vec::new();
+  let mut todo: Option<Vec<Property>> = None;
+  // components nested in a VTODO, like VALARM, don't describe the task
+  let mut nested = 0;
+
+  for line in lines.iter().filter(|line| !line.trim().is_empty()) {
+    let property =
+      Property::parse(line).ok_or_else(|| anyhow!("Invalid calendar line: {}", line))?;
+
+    match (property.name.as_str(), property.value.as_str(), &mut todo) {
+      ("BEGIN", "VTODO", None) => todo = Some(Vec::new()),
+      ("END", "VTODO", Some(_)) if nested == 0 => {
+        if let Some(task) = parse_todo(&todo.take().unwrap(), report) {
+          tasks.push(task);
+        }
+      }
+      ("BEGIN", component, Some(_)) => {
+        report.unmapped(component);
+        nested += 1;
+      }
+      ("END", _, Some(_)) => nested -= 1,
+      (_, _, Some(properties)) if nested == 0 => properties.push(property),
+      _ => {}
+    }
+  }
+
+  if todo.is_some() {
+    return Err(anyhow!("Calendar ends in the middle of a VTODO"));
+  }
+
+  Ok(tasks)
+}
+
+fn parse_todo(properties: &[Property], report: &mut ImportReport) -> Option<ImportedTask> {
+  let get = |name: &str| properties.iter().find(|property| property.name == name);
+
+  let summary = get("SUMMARY").map(|summary| unescape(&summary.value))?;
+  let status = match get("STATUS").map(|status| status.value.as_str()) {
+    Some("NEEDS-ACTION") | None => TaskStatus::Todo,
+    Some("IN-PROCESS") => TaskStatus::InProgress,
+    Some("COMPLETED") => TaskStatus::Done,
+    Some("CANCELLED") => TaskStatus::Cancelled,
+    Some(status) => {
+      let reason = format!("unknown status \"{}\"", status);
+      report.skipped.push((summary, reason));
+      return None;
+    }
+  };
+
+  let mut meta = TaskMeta::parse(&summary);
+  if let Some(due) = get("DUE") {
+    if due.param("TZID").is_some() {
+      report.unmapped("DUE time zone");
+    }
+    if let Some((date, time)) = due.date_time() {
+      meta.due = Some(date);
+      meta.due_time = time;
+    }
+  }
+  if let Some(priority) = get("PRIORITY").and_then(|p| p.value.trim().parse::<u8>().ok()) {
+    // 0 means no priority was set
+    meta.priority = match priority {
+      0 => meta.priority,
+      1..=9 => Some(priority.div_ceil(2).min(4)),
+      _ => {
+        report.unmapped("PRIORITY");
+        meta.priority
+      }
+    };
+  }
+  let categories = properties
+    .iter()
+    .filter(|property| property.name == "CATEGORIES")
+    .flat_map(|property| split_list(&property.value));
+  for category in categories {
+    // tags can't have spaces in them
+    let tag = category.trim().replace(' ', "-");
+    if !tag.is_empty() && !meta.tags.contains(&tag) {
+      meta.tags.push(tag);
+    }
+  }
+  meta.created = get("CREATED")
+    .and_then(Property::date_time)
+    .map(|(date, _)| date);
+  if status.is_completed() {
+    meta.done = get("COMPLETED")
+      .and_then(Property::date_time)
+      .map(|(date, _)| date);
+  }
+
+  for property in properties {
+    let name = property.name.as_str();
+    if !KNOWN_PROPERTIES.contains(&name) && !name.starts_with("X-") {
+      report.unmapped(name);
+    }
+  }
+
+  Some(ImportedTask {
+    uid: get("UID").map(|uid| uid.value.clone()),
+    task: Task {
+      description: meta.to_description(),
+      status,
+    },
+    notes: Vec::new(),
+  })
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn test_write_and_parse_back() {
+    let tasks = vec![
+      Task {
+        description: String::from(
+          "Call Bob, then Alice due:2026-10-19T15:00 p2 #sales #crm @phone",
+        ),
+        status: TaskStatus::Todo,
+      },
+      Task {
+        description: String::from("Pay rent due:2026-10-01 done:2026-10-02"),
+        status: TaskStatus::Done,
+      },
+    ];
+
+    let mut out = Vec::new();
+    write(&mut out, &tasks).unwrap();
+    let calendar = String::from_utf8(out).unwrap();
+    assert!(calendar.contains("SUMMARY:Call Bob\\, then Alice @phone\r\n"));
+    assert!(calendar.contains("DUE:20261019T150000\r\n"));
+    assert!(calendar.contains("PRIORITY:3\r\n"));
+    assert!(calendar.contains("CATEGORIES:sales,crm\r\n"));
+    assert!(calendar.contains("DUE;VALUE=DATE:20261001\r\n"));
+    assert!(calendar.contains("STATUS:COMPLETED\r\n"));
+
+    let mut report = ImportReport::default();
+    let imported = parse(&calendar, &mut report).unwrap();
+    assert_eq!(2, imported.len());
+    for (task, imported) in tasks.iter().zip(imported.iter()) {
+      assert_eq!(task, &imported.task);
+      assert_eq!(Some(task_uid(&task.description)), imported.uid);
+    }
+    assert!(report.unmapped.is_empty());
+  }
+
+  #[test]
+  fn test_parse_calendar_app_export() {
+    let calendar = "BEGIN:VCALENDAR\r\n\
+                    BEGIN:VTODO\r\n\
+                    UID:abc@example.com\r\n\
+                    SUMMARY:Renew the passport before the trip to \r\n \
+                    Lisbon\r\n\
+                    DUE;TZID=Europe/Lisbon:20261101T090000\r\n\
+                    PRIORITY:9\r\n\
+                    CATEGORIES:Travel,Admin\r\n\
+                    LOCATION:Town hall\r\n\
+                    BEGIN:VALARM\r\n\
+                    ACTION:DISPLAY\r\n\
+                    END:VALARM\r\n\
+                    END:VTODO\r\n\
+                    BEGIN:VTODO\r\n\
+                    SUMMARY:Old plan\r\n\
+                    STATUS:CANCELLED\r\n\
+                    END:VTODO\r\n\
+                    END:VCALENDAR\r\n";
+
+    let mut report = ImportReport::default();
+    let imported = parse(calendar, &mut report).unwrap();
+
+    assert_eq!(2, imported.len());
+    assert_eq!(Some(String::from("abc@example.com")), imported[0].uid);
+    assert_eq!(
+      "Renew the passport before the trip to Lisbon due:2026-11-01T09:00 p4 #Travel #Admin",
+      imported[0].task.description
+    );
+    assert_eq!(TaskStatus::Cancelled, imported[1].task.status);
+    assert_eq!(
+      vec!["DUE time zone", "LOCATION", "VALARM"],
+      report.unmapped.keys().collect::<Vec<_>>()
+    );
+  }
+
+  #[test]
+  fn test_imported_uid_is_kept() {
+    let tasks = vec![Task {
+      description: String::from("Renew the passport #admin uid:abc@example.com"),
+      status: TaskStatus::Todo,
+    }];
+
+    let mut out = Vec::new();
+    write(&mut out, &tasks).unwrap();
+    let calendar = String::from_utf8(out).unwrap();
+    assert!(calendar.contains("UID:abc@example.com\r\n"));
+    assert!(calendar.contains("SUMMARY:Renew the passport\r\n"));
+
+    // edited in the calendar app, it still points at the same task
+    let edited = calendar.replace("the passport", "both passports");
+    let imported = parse(&edited, &mut ImportReport::default()).unwrap();
+    assert_eq!(Some(task_uid(&tasks[0].description)), imported[0].uid);
+  }
+
+  #[test]
+  fn test_long_lines_are_folded() {
+    let mut out = Vec::new();
+    write_line(&mut out, &format!("SUMMARY:{}", "é".repeat(60))).unwrap();
+    let folded = String::from_utf8(out).unwrap();
+
+    assert!(folded.split("\r\n").all(|line| line.len() <= 75));
+    assert_eq!(
+      format!("SUMMARY:{}", "é".repeat(60)),
+      folded.replace("\r\n ", "").trim_end()
+    );
+  }
+}
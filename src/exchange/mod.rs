@@ -0,0 +1,209 @@
+use anyhow::Result;
+use log::info;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::io::Write;
+use uuid::Uuid;
+
+use crate::tasks::{
+  GetTasksFilterOption, Task, TaskListPersist, TaskMeta, TaskStatus, TaskUpdateAction,
+};
+
+pub mod ical;
+pub mod taskwarrior;
+
+/// Formats other task managers can read and write
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ExchangeFormat {
+  Taskwarrior,
+  Ical,
+}
+
+/// Namespace for the ids given to exported tasks
+const UID_NAMESPACE: Uuid = Uuid::from_u128(0x6f0b_5c2e_8d4a_4f3e_9b1a_2c7d_e5f4_a3b2);
+
+/// The id a task is exported with: the one it was imported with, kept in
+/// its `uid:` field, or else one derived from the description, which is all
+/// that identifies a task, so it stays the same until the task is edited
+pub fn task_uid(description: &str) -> String {
+  TaskMeta::parse(description)
+    .uid
+    .unwrap_or_else(|| Uuid::new_v5(&UID_NAMESPACE, description.as_bytes()).to_string())
+}
+
+/// `task_uid` for formats where the id has to be a UUID
+pub fn task_uuid(description: &str) -> Uuid {
+  let uid = task_uid(description);
+  Uuid::try_parse(&uid).unwrap_or_else(|_| Uuid::new_v5(&UID_NAMESPACE, uid.as_bytes()))
+}
+
+/// A task read from another format, ready to be merged into a task list
+#[derive(Debug, PartialEq)]
+pub struct ImportedTask {
+  pub uid: Option<String>,
+  pub task: Task,
+  /// Lines to add to the task's notes, e.g. taskwarrior's annotations
+  pub notes: Vec<String>,
+}
+
+/// What an import did, including everything it had to leave out
+#[derive(Debug, Default, PartialEq)]
+pub struct ImportReport {
+  pub added: usize,
+  pub updated: usize,
+  pub unchanged: usize,
+  /// Tasks that weren't imported at all, with the reason why
+  pub skipped: Vec<(String, String)>,
+  /// Fields that couldn't be mapped and how many tasks had them
+  pub unmapped: BTreeMap<String, usize>,
+}
+
+impl ImportReport {
+  pub fn unmapped(&mut self, field: &str) {
+    *self.unmapped.entry(field.to_string()).or_default() += 1;
+  }
+}
+
+impl fmt::Display for ImportReport {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(
+      f,
+      "Imported {} tasks ({} added, {} updated, {} unchanged)",
+      self.added + self.updated + self.unchanged,
+      self.added,
+      self.updated,
+      self.unchanged
+    )?;
+    for (description, reason) in self.skipped.iter() {
+      write!(f, "\nSkipped \"{}\": {}", description, reason)?;
+    }
+    for (field, count) in self.unmapped.iter() {
+      let tasks = if *count == 1 { "task" } else { "tasks" };
+      write!(f, "\nNot imported: {} ({} {})", field, count, tasks)?;
+    }
+
+    Ok(())
+  }
+}
+
+/// Reads tasks in `format` and merges them into the store. A task replaces
+/// the one it was exported from or last imported as when its id matches,
+/// otherwise the one with the same description. Anything else is added.
+/// The id is kept in the task's `uid:` field.
+pub fn import(
+  store: &mut dyn TaskListPersist,
+  format: ExchangeFormat,
+  input: &str,
+) -> Result<ImportReport> {
+  let mut report = ImportReport::default();
+  let imported = match format {
+    ExchangeFormat::Taskwarrior => taskwarrior::parse(input, &mut report)?,
+    ExchangeFormat::Ical => ical::parse(input, &mut report)?,
+  };
+
+  store.lock()?;
+  let mut tasklist = store.load_tasklist()?;
+  let mut by_uid: HashMap<String, String> = tasklist
+    .get_tasks(&GetTasksFilterOption::All)
+    .into_iter()
+    .map(|task| (task_uid(&task.description), task.description))
+    .collect();
+  let mut annotated = Vec::new();
+
+  for ImportedTask {
+    uid,
+    mut task,
+    notes,
+  } in imported
+  {
+    let existing = uid
+      .as_ref()
+      .and_then(|uid| by_uid.get(uid))
+      .cloned()
+      .unwrap_or_else(|| task.description.clone());
+    // a field can't hold spaces, and ids with them are rare enough to match
+    // by description instead
+    if let Some(uid) = uid
+      .as_ref()
+      .filter(|uid| !uid.contains(char::is_whitespace))
+    {
+      let mut meta = TaskMeta::parse(&task.description);
+      meta.uid = Some(uid.clone());
+      task.description = meta.to_description();
+    }
+
+    match tasklist.get_task(&existing) {
+      Some(current) if current == task => report.unchanged += 1,
+      Some(current) => {
+        if current.description != task.description {
+          if tasklist.get_task(&task.description).is_some() {
+            let reason = String::from("another task already has this description");
+            report.skipped.push((task.description, reason));
+            continue;
+          }
+          tasklist.update_task(
+            TaskUpdateAction::Edit(&task.description),
+            &current.description,
+          );
+        }
+        if current.status != task.status {
+          tasklist.update_task(TaskUpdateAction::SetStatus(task.status), &task.description);
+        }
+        report.updated += 1;
+      }
+      None => {
+        if let Err(err) = tasklist.add_task(task.description.clone()) {
+          report.skipped.push((task.description, err.to_string()));
+          continue;
+        }
+        if task.status != TaskStatus::Todo {
+          tasklist.update_task(TaskUpdateAction::SetStatus(task.status), &task.description);
+        }
+        report.added += 1;
+      }
+    }
+
+    if !notes.is_empty() {
+      annotated.push((task.description.clone(), notes));
+    }
+    if let Some(uid) = uid {
+      by_uid.insert(uid, task.description);
+    }
+  }
+
+  store.save_tasklist(&mut tasklist)?;
+  // notes go under tasks the store has, so only once they're saved
+  for (description, notes) in annotated {
+    // not every store keeps notes
+    let Ok(mut current) = store.load_notes(&description) else {
+      report.unmapped("notes");
+      continue;
+    };
+    // the same notes come again when a task is imported again
+    let new: Vec<String> = notes
+      .into_iter()
+      .filter(|note| !current.contains(note))
+      .collect();
+    if !new.is_empty() {
+      current.extend(new);
+      store.save_notes(&description, &current)?;
+    }
+  }
+  info!("imported {} tasks", report.added + report.updated);
+
+  Ok(report)
+}
+
+/// Writes every task in the store in `format`
+pub fn export<W: Write>(
+  store: &mut dyn TaskListPersist,
+  format: ExchangeFormat,
+  out: &mut W,
+) -> Result<()> {
+  let tasks = store.load_tasklist()?.get_tasks(&GetTasksFilterOption::All);
+
+  match format {
+    ExchangeFormat::Taskwarrior => taskwarrior::write(out, &tasks),
+    ExchangeFormat::Ical => ical::write(out, &tasks),
+  }
+}
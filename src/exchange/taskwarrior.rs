@@ -0,0 +1,342 @@
+use anyhow::{Result, anyhow};
+use chrono::{Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+use serde_json::{Map, Value, json};
+use std::io::Write;
+
+use super::{ImportReport, ImportedTask, task_uuid};
+use crate::tasks::meta::format_estimate;
+use crate::tasks::{Task, TaskMeta, TaskStatus};
+
+/// Taskwarrior keeps every date in UTC, e.g. `20261019T130000Z`
+const DATE_FORMAT: &str = "%Y%m%dT%H%M%SZ";
+
+/// Fields that are mapped onto a task, or that taskwarrior works out itself
+/// and so aren't worth reporting when they're dropped
+const KNOWN_FIELDS: &[&str] = &[
+  "uuid",
+  "status",
+  "description",
+  "due",
+  "scheduled",
+  "wait",
+  "estimate",
+  "priority",
+  "tags",
+  "project",
+  "annotations",
+  "entry",
+  "end",
+  "start",
+  "id",
+  "urgency",
+  "modified",
+];
+
+fn parse_date(value: Option<&Value>) -> Option<NaiveDateTime> {
+  let date = NaiveDateTime::parse_from_str(value?.as_str()?, DATE_FORMAT).ok()?;
+  Some(
+    Utc
+      .from_utc_datetime(&date)
+      .with_timezone(&Local)
+      .naive_local(),
+  )
+}
+
+/// Reads an `estimate` UDA, a duration such as `PT1H30M`
+fn parse_duration(value: Option<&Value>) -> Option<i64> {
+  let duration = value?.as_str()?.strip_prefix("PT")?;
+  TaskMeta::parse(&format!("~{}", duration.to_lowercase())).estimate
+}
+
+fn format_date(date: NaiveDate, time: Option<NaiveTime>) -> Option<String> {
+  let date = date.and_time(time.unwrap_or_default());
+  let date = Local.from_local_datetime(&date).earliest()?;
+  Some(date.with_timezone(&Utc).format(DATE_FORMAT).to_string())
+}
+
+/// Reads the output of `task export`, either a JSON array or one task per line
+pub fn parse(input: &str, report: &mut ImportReport) -> Result<Vec<ImportedTask>> {
+  let values: Vec<Value> = if input.trim_start().starts_with('[') {
+    serde_json::from_str(input)?
+  } else {
+    input
+      .lines()
+      .filter(|line| !line.trim().is_empty())
+      .map(serde_json::from_str)
+      .collect::<serde_json::Result<_>>()?
+  };
+
+  let mut tasks = Vec::new();
+  for value in values {
+    let fields = value
+      .as_object()
+      .ok_or_else(|| anyhow!("Expected a task object, found {}", value))?;
+    if let Some(task) = parse_task(fields, report)? {
+      tasks.push(task);
+    }
+  }
+
+  Ok(tasks)
+}
+
+fn parse_task(
+  fields: &Map<String, Value>,
+  report: &mut ImportReport,
+) -> Result<Option<ImportedTask>> {
+  let description = fields
+    .get("description")
+    .and_then(Value::as_str)
+    .ok_or_else(|| anyhow!("Task without a description: {:?}", fields))?;
+
+  let status = match fields.get("status").and_then(Value::as_str) {
+    // a start time marks the task as active
+    Some("pending") | None if fields.contains_key("start") => TaskStatus::InProgress,
+    Some("pending") | None => TaskStatus::Todo,
+    Some("waiting") => TaskStatus::Deferred,
+    Some("completed") => TaskStatus::Done,
+    Some("deleted") => TaskStatus::Cancelled,
+    Some("recurring") => {
+      let reason = String::from("recurring template, its instances are imported instead");
+      report.skipped.push((description.to_string(), reason));
+      return Ok(None);
+    }
+    Some(status) => {
+      let reason = format!("unknown status \"{}\"", status);
+      report.skipped.push((description.to_string(), reason));
+      return Ok(None);
+    }
+  };
+
+  let mut meta = TaskMeta::parse(description);
+  if let Some(due) = parse_date(fields.get("due")) {
+    meta.due = Some(due.date());
+    meta.due_time = Some(due.time()).filter(|time| *time != NaiveTime::MIN);
+  }
+  let date = |field: &str| parse_date(fields.get(field)).map(|date| date.date());
+  meta.scheduled = date("scheduled").or(meta.scheduled);
+  // a task waits in taskwarrior until it can be started
+  meta.start = date("wait").or(meta.start);
+  meta.estimate = parse_duration(fields.get("estimate")).or(meta.estimate);
+  match fields.get("priority").and_then(Value::as_str) {
+    Some("H") => meta.priority = Some(1),
+    Some("M") => meta.priority = Some(2),
+    Some("L") => meta.priority = Some(3),
+    Some(_) => report.unmapped("priority"),
+    None => {}
+  }
+  let project = fields
+    .get("project")
+    .and_then(Value::as_str)
+    // `home.garden` is a subproject, written as a nested tag
+    .map(|project| project.replace('.', "/"));
+  let tags = fields
+    .get("tags")
+    .and_then(Value::as_array)
+    .into_iter()
+    .flatten()
+    .filter_map(Value::as_str)
+    .map(str::to_string);
+  for tag in tags.chain(project) {
+    if !meta.tags.contains(&tag) {
+      meta.tags.push(tag);
+    }
+  }
+  meta.created = parse_date(fields.get("entry")).map(|entry| entry.date());
+  if status.is_completed() {
+    meta.done = parse_date(fields.get("end")).map(|end| end.date());
+  }
+
+  // notes under the task, dated as `task info` shows them
+  let notes = fields
+    .get("annotations")
+    .and_then(Value::as_array)
+    .into_iter()
+    .flatten()
+    .filter_map(|annotation| {
+      let description = annotation.get("description")?.as_str()?;
+      Some(match parse_date(annotation.get("entry")) {
+        Some(entry) => format!("{} {}", entry.format("%Y-%m-%d"), description),
+        None => description.to_string(),
+      })
+    })
+    .collect();
+  for field in fields.keys() {
+    if !KNOWN_FIELDS.contains(&field.as_str()) {
+      report.unmapped(field);
+    }
+  }
+
+  Ok(Some(ImportedTask {
+    uid: fields
+      .get("uuid")
+      .and_then(Value::as_str)
+      .map(str::to_string),
+    task: Task {
+      description: meta.to_description(),
+      status,
+    },
+    notes,
+  }))
+}
+
+/// Writes tasks as a JSON array that `task import` understands
+pub fn write<W: Write>(out: &mut W, tasks: &[Task]) -> Result<()> {
+  let mut values = Vec::new();
+
+  for task in tasks {
+    let meta = TaskMeta::parse(&task.description);
+    // there are no fields for these so they stay in the description
+    let description = TaskMeta {
+      title: meta.title.clone(),
+      context: meta.context.clone(),
+      recurrence: meta.recurrence.clone(),
+      emoji: meta.emoji,
+      ..TaskMeta::default()
+    }
+    .to_description();
+
+    let mut fields = Map::new();
+    fields.insert(
+      String::from("uuid"),
+      json!(task_uuid(&task.description).to_string()),
+    );
+    fields.insert(String::from("description"), json!(description));
+    let status = match task.status {
+      TaskStatus::Done => "completed",
+      TaskStatus::Cancelled => "deleted",
+      _ => "pending",
+    };
+    // `start` is when work began, which isn't known, so in progress is
+    // exported as pending
+    fields.insert(String::from("status"), json!(status));
+
+    if let Some(due) = meta.due.and_then(|due| format_date(due, meta.due_time)) {
+      fields.insert(String::from("due"), json!(due));
+    }
+    if let Some(scheduled) = meta
+      .scheduled
+      .and_then(|scheduled| format_date(scheduled, None))
+    {
+      fields.insert(String::from("scheduled"), json!(scheduled));
+    }
+    if let Some(wait) = meta.start.and_then(|start| format_date(start, None)) {
+      fields.insert(String::from("wait"), json!(wait));
+    }
+    // not a taskwarrior field, it's kept as a UDA of type duration
+    if let Some(estimate) = meta.estimate {
+      let estimate = format!("PT{}", format_estimate(estimate).to_uppercase());
+      fields.insert(String::from("estimate"), json!(estimate));
+    }
+    if let Some(priority) = meta.priority {
+      let priority = match priority {
+        1 => "H",
+        2 => "M",
+        _ => "L",
+      };
+      fields.insert(String::from("priority"), json!(priority));
+    }
+    let project = meta.tags.iter().find(|tag| tag.contains('/'));
+    if let Some(project) = project {
+      fields.insert(String::from("project"), json!(project.replace('/', ".")));
+    }
+    let tags: Vec<&String> = meta
+      .tags
+      .iter()
+      .filter(|tag| Some(*tag) != project)
+      .collect();
+    if !tags.is_empty() {
+      fields.insert(String::from("tags"), json!(tags));
+    }
+    if let Some(entry) = meta.created.and_then(|created| format_date(created, None)) {
+      fields.insert(String::from("entry"), json!(entry));
+    }
+    if let Some(end) = meta.done.and_then(|done| format_date(done, None)) {
+      fields.insert(String::from("end"), json!(end));
+    }
+
+    values.push(Value::Object(fields));
+  }
+
+  serde_json::to_writer_pretty(&mut *out, &values)?;
+  writeln!(out)?;
+
+  Ok(())
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn test_parse() {
+    let due = format_date(NaiveDate::from_ymd_opt(2026, 10, 19).unwrap(), None).unwrap();
+    let scheduled = format_date(NaiveDate::from_ymd_opt(2026, 10, 18).unwrap(), None).unwrap();
+    let input = format!(
+      r#"[
+        {{"id":1,"uuid":"4a3c","status":"pending","description":"Fix the fence",
+          "due":"{due}","priority":"H","project":"home.garden","tags":["diy"],
+          "annotations":[{{"entry":"20261001T100000Z","description":"buy nails"}}],
+          "scheduled":"{scheduled}","estimate":"PT1H30M","until":"20261101T000000Z",
+          "urgency":8.2}},
+        {{"uuid":"5b4d","status":"completed","description":"Pay rent"}},
+        {{"uuid":"6c5e","status":"deleted","description":"Old idea"}},
+        {{"uuid":"7d6f","status":"recurring","description":"Standup"}}
+      ]"#
+    );
+
+    let mut report = ImportReport::default();
+    let tasks = parse(&input, &mut report).unwrap();
+
+    assert_eq!(3, tasks.len());
+    assert_eq!(Some(String::from("4a3c")), tasks[0].uid);
+    assert_eq!(
+      "Fix the fence due:2026-10-19 p1 #diy #home/garden ~1h30m scheduled:2026-10-18",
+      tasks[0].task.description
+    );
+    assert_eq!(TaskStatus::Todo, tasks[0].task.status);
+    assert_eq!(vec!["2026-10-01 buy nails"], tasks[0].notes);
+    assert_eq!(TaskStatus::Done, tasks[1].task.status);
+    assert_eq!(TaskStatus::Cancelled, tasks[2].task.status);
+    assert_eq!("Standup", report.skipped[0].0);
+    assert_eq!(
+      vec![("until", 1)],
+      report
+        .unmapped
+        .iter()
+        .map(|(field, count)| (field.as_str(), *count))
+        .collect::<Vec<_>>()
+    );
+  }
+
+  #[test]
+  fn test_write_and_parse_back() {
+    let tasks = vec![Task {
+      description: String::from(
+        "Call Bob due:2026-10-19T15:00 p2 #sales #work/crm @phone ~45m created:2026-10-01 \
+         scheduled:2026-10-17 start:2026-10-16",
+      ),
+      status: TaskStatus::InProgress,
+    }];
+
+    let mut out = Vec::new();
+    write(&mut out, &tasks).unwrap();
+    let exported: Value = serde_json::from_slice(&out).unwrap();
+    assert_eq!("Call Bob @phone", exported[0]["description"]);
+    assert_eq!("work.crm", exported[0]["project"]);
+    assert_eq!(json!(["sales"]), exported[0]["tags"]);
+    assert_eq!("PT45M", exported[0]["estimate"]);
+    assert!(exported[0].get("start").is_none());
+    assert_eq!(
+      task_uuid(&tasks[0].description).to_string(),
+      exported[0]["uuid"]
+    );
+
+    let mut report = ImportReport::default();
+    let imported = parse(&String::from_utf8(out).unwrap(), &mut report).unwrap();
+    assert_eq!(
+      TaskMeta::parse(&tasks[0].description),
+      TaskMeta::parse(&imported[0].task.description)
+    );
+    assert!(report.unmapped.is_empty());
+  }
+}
@@ -0,0 +1,252 @@
+use anyhow::Result;
+use chrono::{Local, NaiveDate};
+use console::Term;
+use log::info;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::config::Config;
+use crate::editor;
+use crate::journal::note;
+use crate::storage;
+use crate::tasks::{
+  GetTasksFilterOption, TaskList, TaskListPersist, TaskMeta, TaskStatus, TaskUpdateAction,
+};
+use crate::timer::{GroupBy, TimeReport};
+
+const DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// The checklist items already picked up from the notes, one per line, so
+/// the ones deleted from the store since don't come back
+const PICKED_UP_FILE: &str = ".picked-up";
+
+pub struct JournalController<S: TaskListPersist> {
+  storage: S,
+  config: Config,
+  /// Where the notes are kept, one per day
+  dir: PathBuf,
+  /// Add checklist items from the notes to the store, for stores that
+  /// don't read the notes themselves
+  pick_up: bool,
+}
+
+impl<S: TaskListPersist> JournalController<S> {
+  pub fn new(
+    mut storage: S,
+    config: Config,
+    dir: PathBuf,
+    pick_up: bool,
+  ) -> Result<JournalController<S>> {
+    storage.lock()?;
+    Ok(JournalController {
+      storage,
+      config,
+      dir,
+      pick_up,
+    })
+  }
+
+  /// Opens the note for `date` in the editor, creating it first with the
+  /// open tasks of the last note rolled over into it
+  pub fn open(&mut self, date: NaiveDate, summarise: bool) -> Result<()> {
+    let path = self.path(date);
+    if !path.exists() {
+      self.create(date, &path)?;
+    }
+    if summarise {
+      self.summarise(date, &path)?;
+    }
+
+    // nothing else needs waiting on while the note is being written
+    self.storage.unlock();
+    let edited = Term::stdout().is_term() && editor::edit(&path)?;
+    self.storage.lock()?;
+    if !edited {
+      println!("{}", path.display());
+    }
+
+    if self.pick_up {
+      self.pick_up(&path)?;
+    }
+
+    Ok(())
+  }
+
+  fn path(&self, date: NaiveDate) -> PathBuf {
+    self.dir.join(format!("{}.md", date.format(DATE_FORMAT)))
+  }
+
+  /// The note of the last day before `date` that has one
+  fn previous(&self, date: NaiveDate) -> Result<Option<PathBuf>> {
+    let entries = match fs::read_dir(&self.dir) {
+      Ok(entries) => entries,
+      Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+      Err(err) => return Err(err.into()),
+    };
+
+    let mut previous: Option<(NaiveDate, PathBuf)> = None;
+    for entry in entries {
+      let path = entry?.path();
+      let Some(day) = path
+        .file_name()
+        .and_then(|name| name.to_str()?.strip_suffix(".md"))
+        .and_then(|stem| NaiveDate::parse_from_str(stem, DATE_FORMAT).ok())
+      else {
+        continue;
+      };
+      if day < date && previous.as_ref().is_none_or(|(last, _)| day > *last) {
+        previous = Some((day, path));
+      }
+    }
+
+    Ok(previous.map(|(_, path)| path))
+  }
+
+  fn create(&mut self, date: NaiveDate, path: &Path) -> Result<()> {
+    let template = match &self.config.journal.template {
+      Some(template) => fs::read_to_string(template)?,
+      None => String::from(note::DEFAULT_TEMPLATE),
+    };
+
+    let mut rolled = Vec::new();
+    if let Some(previous) = self.previous(date)? {
+      let (mut lines, ending) = read_lines(&previous)?;
+      rolled = note::take_open_tasks(&mut lines);
+      if !rolled.is_empty() {
+        info!(
+          "rolling {} lines over from {}",
+          rolled.len(),
+          previous.display()
+        );
+        self.write_lines(&previous, &lines, ending)?;
+      }
+    }
+
+    fs::create_dir_all(&self.dir)?;
+    let lines = note::render(&template, date, &rolled);
+    self.write_lines(path, &lines, line_ending(&template))?;
+    println!("Created {}", path.display());
+
+    Ok(())
+  }
+
+  /// Adds the tasks completed on `date` and the time logged that day
+  fn summarise(&mut self, date: NaiveDate, path: &Path) -> Result<()> {
+    let now = Local::now();
+    let (mut lines, ending) = read_lines(path)?;
+    // not every store keeps time
+    let entries = self.storage.load_time_entries().unwrap_or_default();
+    let worked_on = |description: &str| {
+      entries.iter().any(|time| {
+        time.task == description
+          && time.entry.start.date_naive() <= date
+          && time.entry.end.unwrap_or(now).date_naive() >= date
+      })
+    };
+
+    let mut completed: Vec<String> = TaskList::md_tasks(&lines)
+      .filter(|(_, (_, _, status, _))| status.is_completed())
+      .map(|(_, (_, _, _, description))| description.trim().to_string())
+      .collect();
+    for task in self
+      .storage
+      .load_tasklist()?
+      .get_tasks(&GetTasksFilterOption::Completed)
+    {
+      let done_that_day = TaskMeta::parse(&task.description).done == Some(date);
+      if task.status.is_completed()
+        && (done_that_day || worked_on(&task.description))
+        && !completed.contains(&task.description)
+      {
+        completed.push(task.description);
+      }
+    }
+
+    let report = TimeReport::build(
+      &entries,
+      date,
+      date,
+      GroupBy::Task,
+      &self.config.report,
+      now,
+    );
+    note::set_summary(&mut lines, note::summary(&completed, &report)?);
+    self.write_lines(path, &lines, ending)
+  }
+
+  /// Adds the note's checklist items the store doesn't have and never had,
+  /// and closes the ones closed in the note. Nothing is reopened, as that
+  /// may have been done since outside the note.
+  fn pick_up(&mut self, path: &Path) -> Result<()> {
+    let (lines, _) = read_lines(path)?;
+    let mut tasklist = self.storage.load_tasklist()?;
+    let mut added = 0;
+
+    let record = self.dir.join(PICKED_UP_FILE);
+    let mut picked_up: Vec<String> = match fs::read_to_string(&record) {
+      Ok(contents) => contents.lines().map(String::from).collect(),
+      Err(err) if err.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+      Err(err) => return Err(err.into()),
+    };
+    let known = picked_up.len();
+
+    for (_, (_, _, status, description)) in TaskList::md_tasks(&lines) {
+      let description = description.trim();
+      let is_new = !picked_up.iter().any(|known| known == description);
+      if is_new {
+        picked_up.push(description.to_string());
+      }
+      match tasklist.get_task(description) {
+        None if is_new => {
+          tasklist.add_task(description.to_string())?;
+          if status != TaskStatus::Todo {
+            tasklist.update_task(TaskUpdateAction::SetStatus(status), description);
+          }
+          added += 1;
+        }
+        Some(task) if status.is_closed() && !task.status.is_closed() => {
+          tasklist.update_task(TaskUpdateAction::SetStatus(status), description);
+        }
+        _ => {}
+      }
+    }
+
+    if picked_up.len() > known {
+      let mut contents = picked_up.join("\n");
+      contents.push('\n');
+      storage::write_atomic(&record.to_string_lossy(), &contents, false)?;
+    }
+
+    if tasklist.has_changes() {
+      self.storage.save_tasklist(&mut tasklist)?;
+    }
+    if added > 0 {
+      println!("Picked up {} tasks from {}", added, path.display());
+    }
+
+    Ok(())
+  }
+
+  /// Writes a note the way the stores write their files, with a backup
+  /// when they keep one
+  fn write_lines(&self, path: &Path, lines: &[String], ending: &str) -> Result<()> {
+    let mut contents = lines.join(ending);
+    contents.push_str(ending);
+    storage::write_atomic(&path.to_string_lossy(), &contents, self.config.backup)
+  }
+}
+
+/// The note's lines and the line ending it uses, so it's written back with it
+fn read_lines(path: &Path) -> Result<(Vec<String>, &'static str)> {
+  let contents = fs::read_to_string(path)?;
+  let lines = contents.lines().map(String::from).collect();
+  Ok((lines, line_ending(&contents)))
+}
+
+fn line_ending(contents: &str) -> &'static str {
+  if contents.contains("\r\n") {
+    "\r\n"
+  } else {
+    "\n"
+  }
+}
@@ -0,0 +1,4 @@
+pub mod controller;
+pub use controller::JournalController;
+
+pub mod note;
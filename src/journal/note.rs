@@ -0,0 +1,222 @@
+use anyhow::Result;
+use chrono::NaiveDate;
+
+use crate::markdown::block_end;
+use crate::tasks::{OutputFormat, TaskList};
+use crate::timer::TimeReport;
+
+/// Used when no template is configured
+pub const DEFAULT_TEMPLATE: &str = "# {{weekday}} {{date}}\n\n## Tasks\n\n{{tasks}}\n\n## Notes\n";
+
+const SUMMARY_HEADING: &str = "## Summary";
+
+/// Fills in `{{date}}`, `{{weekday}}` and `{{tasks}}`, the tasks rolled over
+/// from the last note. They're added at the end when the template has no
+/// `{{tasks}}` line.
+pub fn render(template: &str, date: NaiveDate, tasks: &[String]) -> Vec<String> {
+  let date_str = date.format("%Y-%m-%d").to_string();
+  let weekday = date.format("%A").to_string();
+  let mut lines = Vec::new();
+  let mut placed = false;
+
+  for line in template.lines() {
+    if line.trim() == "{{tasks}}" {
+      lines.extend(tasks.iter().cloned());
+      placed = true;
+      continue;
+    }
+    lines.push(
+      line
+        .replace("{{date}}", &date_str)
+        .replace("{{weekday}}", &weekday),
+    );
+  }
+
+  if !placed && !tasks.is_empty() {
+    lines.push(String::new());
+    lines.extend(tasks.iter().cloned());
+  }
+
+  lines
+}
+
+/// Takes the open tasks out of a note, each with the lines indented under
+/// it such as notes and time entries, and unindents them
+pub fn take_open_tasks(lines: &mut Vec<String>) -> Vec<String> {
+  let open: Vec<(usize, usize)> = TaskList::md_tasks(lines)
+    .filter(|(_, (_, _, status, _))| !status.is_closed())
+    .map(|(i, (start, ..))| (i, start))
+    .collect();
+
+  let mut blocks = Vec::new();
+  for (line, start) in open.into_iter().rev() {
+    let end = block_end(lines, line, start);
+    let block: Vec<String> = lines
+      .drain(line..end)
+      .map(|text| match text.get(..start) {
+        Some(indent) if indent.trim().is_empty() => text[start..].to_string(),
+        _ => text.trim_start().to_string(),
+      })
+      .collect();
+    blocks.push(block);
+  }
+
+  blocks.into_iter().rev().flatten().collect()
+}
+
+/// The day's completed tasks and a table of the time logged, under a heading
+pub fn summary(completed: &[String], time: &TimeReport) -> Result<Vec<String>> {
+  let mut lines = vec![String::from(SUMMARY_HEADING), String::new()];
+
+  lines.push(String::from("Completed:"));
+  lines.push(String::new());
+  if completed.is_empty() {
+    lines.push(String::from("- nothing yet"));
+  }
+  // plain bullets so the summary isn't read as more tasks
+  lines.extend(completed.iter().map(|task| format!("- {}", task)));
+  lines.push(String::new());
+
+  if time.rows.is_empty() {
+    lines.push(String::from("No time logged."));
+  } else {
+    let mut table = Vec::new();
+    time.write(&mut table, OutputFormat::Markdown)?;
+    lines.extend(String::from_utf8(table)?.lines().map(String::from));
+  }
+
+  Ok(lines)
+}
+
+/// Replaces the summary in the note, up to the next heading, or adds it to
+/// the end
+pub fn set_summary(lines: &mut Vec<String>, summary: Vec<String>) {
+  let Some(start) = lines
+    .iter()
+    .position(|line| line.trim_end() == SUMMARY_HEADING)
+  else {
+    if lines.last().is_some_and(|line| !line.trim().is_empty()) {
+      lines.push(String::new());
+    }
+    lines.extend(summary);
+    return;
+  };
+
+  let end = lines
+    .iter()
+    .enumerate()
+    .skip(start + 1)
+    .find(|(_, line)| line.starts_with("# ") || line.starts_with("## "))
+    .map_or(lines.len(), |(i, _)| i);
+  let mut summary = summary;
+  if end < lines.len() {
+    summary.push(String::new());
+  }
+  lines.splice(start..end, summary);
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use crate::config::ReportConfig;
+  use crate::timer::{GroupBy, TaskTime, TimeEntry};
+  use chrono::Local;
+
+  fn lines(text: &str) -> Vec<String> {
+    text.lines().map(String::from).collect()
+  }
+
+  fn date() -> NaiveDate {
+    NaiveDate::from_ymd_opt(2026, 10, 17).unwrap()
+  }
+
+  #[test]
+  fn test_render() {
+    let tasks = lines("- [ ] call Bob");
+
+    assert_eq!(
+      lines("# Saturday 2026-10-17\n\n## Tasks\n\n- [ ] call Bob\n\n## Notes"),
+      render(DEFAULT_TEMPLATE, date(), &tasks)
+    );
+    assert_eq!(
+      lines("Log for {{nothing}} 2026-10-17\n\n- [ ] call Bob"),
+      render("Log for {{nothing}} {{date}}\n", date(), &tasks)
+    );
+  }
+
+  #[test]
+  fn test_take_open_tasks() {
+    let mut note = lines(
+      "# Friday\n\
+       - [x] done\n\
+       \x20 - a note\n\
+       - [ ] call Bob\n\
+       \x20 - about the spec\n\
+       \x20 - ⏱ 2026-10-16 09:00-09:30\n\
+       \n\
+       Some thoughts\n\
+       \x20 - [/] nested #api\n\
+       - [-] dropped",
+    );
+
+    assert_eq!(
+      lines(
+        "- [ ] call Bob\n\
+         \x20 - about the spec\n\
+         \x20 - ⏱ 2026-10-16 09:00-09:30\n\
+         - [/] nested #api"
+      ),
+      take_open_tasks(&mut note)
+    );
+    assert_eq!(
+      lines("# Friday\n- [x] done\n  - a note\n\nSome thoughts\n- [-] dropped"),
+      note
+    );
+  }
+
+  #[test]
+  fn test_set_summary() {
+    let entries = [TaskTime {
+      task: String::from("call Bob"),
+      section: None,
+      entry: TimeEntry::parse("2026-10-17 09:00-09:30").unwrap(),
+    }];
+    let report = TimeReport::build(
+      &entries,
+      date(),
+      date(),
+      GroupBy::Task,
+      &ReportConfig::default(),
+      Local::now(),
+    );
+    let mut note = lines("# Saturday\n\n## Summary\n\nold\n\n## Notes\nkept");
+
+    set_summary(
+      &mut note,
+      summary(&[String::from("call Bob")], &report).unwrap(),
+    );
+    assert_eq!(
+      lines(
+        "# Saturday\n\n## Summary\n\nCompleted:\n\n- call Bob\n\n\
+         | Task | Time | Hours |\n| --- | ---: | ---: |\n| call Bob | 0:30 | 0.50 |\n\
+         | **Total** | **0:30** | **0.50** |\n\n## Notes\nkept"
+      ),
+      note
+    );
+
+    let mut note = lines("# Saturday");
+    let empty = TimeReport::build(
+      &[],
+      date(),
+      date(),
+      GroupBy::Task,
+      &ReportConfig::default(),
+      Local::now(),
+    );
+    set_summary(&mut note, summary(&[], &empty).unwrap());
+    assert_eq!(
+      lines("# Saturday\n\n## Summary\n\nCompleted:\n\n- nothing yet\n\nNo time logged."),
+      note
+    );
+  }
+}
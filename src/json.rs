@@ -0,0 +1,318 @@
+use anyhow::{Result, anyhow};
+use chrono::{DateTime, Local};
+use log::{debug, info};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+use uuid::Uuid;
+
+use crate::storage;
+use crate::tasks::{Task, TaskChange, TaskList, TaskListPersist, TaskMeta, TaskStatus};
+use crate::timer::{TaskTime, TimeEntry};
+
+const FORMAT_VERSION: u32 = 2;
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+struct Document {
+  version: u32,
+  tasks: Vec<Record>,
+}
+
+/// Everything kept about a task, including what can't be written in markdown
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Record {
+  pub id: String,
+  pub description: String,
+  #[serde(default)]
+  pub status: TaskStatus,
+  /// Only written by version 1, from before there were other statuses
+  #[serde(default, skip_serializing)]
+  is_completed: bool,
+  pub created: DateTime<Local>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub completed: Option<DateTime<Local>>,
+  #[serde(default)]
+  pub tags: Vec<String>,
+  #[serde(default)]
+  pub notes: Vec<String>,
+  #[serde(default)]
+  pub time_entries: Vec<TimeEntry>,
+}
+
+impl Record {
+  fn new(task: Task) -> Record {
+    let now = Local::now();
+    Record {
+      id: Uuid::new_v4().to_string(),
+      tags: TaskMeta::parse(&task.description).tags,
+      completed: task.status.is_completed().then_some(now),
+      description: task.description,
+      status: task.status,
+      is_completed: false,
+      created: now,
+      notes: Vec::new(),
+      time_entries: Vec::new(),
+    }
+  }
+
+  fn update(&mut self, task: Task) {
+    if task.status.is_completed() != self.status.is_completed() {
+      self.completed = task.status.is_completed().then(Local::now);
+    }
+    self.tags = TaskMeta::parse(&task.description).tags;
+    self.description = task.description;
+    self.status = task.status;
+  }
+}
+
+pub struct File<'a> {
+  pub path: &'a str,
+  records: Vec<Record>,
+  backup: bool,
+  lock: Option<fs::File>,
+}
+
+impl<'a> File<'a> {
+  pub fn from(path: &'a str) -> File<'a> {
+    File {
+      path,
+      records: Vec::new(),
+      backup: false,
+      lock: None,
+    }
+  }
+
+  /// Keep the previous version of the file as `<path>.bak` on every save
+  pub fn with_backup(mut self, backup: bool) -> File<'a> {
+    self.backup = backup;
+    self
+  }
+
+  pub fn read_file(&mut self) -> Result<()> {
+    let contents = match fs::read_to_string(self.path) {
+      Ok(contents) => contents,
+      // created on the first save
+      Err(err) if err.kind() == ErrorKind::NotFound => {
+        self.records.clear();
+        return Ok(());
+      }
+      Err(err) => return Err(err.into()),
+    };
+
+    let document: Document = serde_json::from_str(&contents)
+      .map_err(|err| anyhow!("Invalid tasks file {}: {}", self.path, err))?;
+    if document.version > FORMAT_VERSION {
+      return Err(anyhow!(
+        "{} was written by a newer version of taskmaster",
+        self.path
+      ));
+    }
+    self.records = document.tasks;
+    if document.version < 2 {
+      for record in self.records.iter_mut().filter(|record| record.is_completed) {
+        record.status = TaskStatus::Done;
+      }
+    }
+
+    Ok(())
+  }
+
+  pub fn write_file(&self) -> Result<()> {
+    let document = Document {
+      version: FORMAT_VERSION,
+      tasks: self.records.clone(),
+    };
+    let mut contents = serde_json::to_string_pretty(&document)?;
+    contents.push('\n');
+
+    storage::write_atomic(self.path, &contents, self.backup)
+  }
+
+  fn apply_changes(&mut self, changes: Vec<TaskChange>) {
+    for change in changes {
+      debug!("applying {:?}", change);
+      match change {
+        TaskChange::Added(task) => self.records.push(Record::new(task)),
+        TaskChange::Updated { original, task } => {
+          if let Some(record) = self.records.iter_mut().find(|r| r.description == original) {
+            record.update(task);
+          }
+        }
+        TaskChange::Deleted(original) => self.records.retain(|r| r.description != original),
+      }
+    }
+  }
+}
+
+impl<'a> TaskListPersist for File<'a> {
+  fn lock(&mut self) -> Result<()> {
+    self.lock = Some(storage::lock(self.path)?);
+    Ok(())
+  }
+
+  fn unlock(&mut self) {
+    self.lock = None;
+  }
+
+  fn watch_path(&self) -> Option<PathBuf> {
+    Some(PathBuf::from(self.path))
+  }
+
+  fn load_tasklist(&mut self) -> Result<TaskList> {
+    info!("loading tasks from json file");
+    self.read_file()?;
+
+    let tasks = self
+      .records
+      .iter()
+      .map(|record| Task {
+        description: record.description.clone(),
+        status: record.status,
+      })
+      .collect();
+
+    Ok(TaskList::from(tasks))
+  }
+
+  fn save_tasklist(&mut self, tasklist: &mut TaskList) -> Result<()> {
+    self.apply_changes(tasklist.changes());
+    self.write_file()
+  }
+
+  fn load_time_entries(&mut self) -> Result<Vec<TaskTime>> {
+    self.read_file()?;
+
+    let entries = self
+      .records
+      .iter()
+      .flat_map(|record| {
+        record.time_entries.iter().map(|entry| TaskTime {
+          task: record.description.clone(),
+          section: None,
+          entry: entry.clone(),
+        })
+      })
+      .collect();
+
+    Ok(entries)
+  }
+
+  fn save_time_entries(&mut self, description: &str, entries: &[TimeEntry]) -> Result<()> {
+    self.read_file()?;
+    let record = self
+      .records
+      .iter_mut()
+      .find(|record| record.description == description)
+      .ok_or_else(|| anyhow!("No task \"{}\" in {}", description, self.path))?;
+    record.time_entries = entries.to_vec();
+
+    self.write_file()
+  }
+
+  fn load_notes(&mut self, description: &str) -> Result<Vec<String>> {
+    self.read_file()?;
+    self
+      .records
+      .iter()
+      .find(|record| record.description == description)
+      .map(|record| record.notes.clone())
+      .ok_or_else(|| anyhow!("No task \"{}\" in {}", description, self.path))
+  }
+
+  fn save_notes(&mut self, description: &str, notes: &[String]) -> Result<()> {
+    self.read_file()?;
+    let record = self
+      .records
+      .iter_mut()
+      .find(|record| record.description == description)
+      .ok_or_else(|| anyhow!("No task \"{}\" in {}", description, self.path))?;
+    record.notes = notes.to_vec();
+
+    self.write_file()
+  }
+
+  fn load_all_notes(&mut self) -> Result<HashMap<String, Vec<String>>> {
+    self.read_file()?;
+    Ok(
+      self
+        .records
+        .iter()
+        .filter(|record| !record.notes.is_empty())
+        .map(|record| (record.description.clone(), record.notes.clone()))
+        .collect(),
+    )
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use crate::tasks::{GetTasksFilterOption, TaskUpdateAction};
+
+  #[test]
+  fn test_save_and_load() {
+    let dir = std::env::temp_dir().join(format!("taskmaster-json-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("tasks.json").to_string_lossy().to_string();
+
+    let mut file = File::from(&path);
+    let mut tasklist = file.load_tasklist().unwrap();
+    tasklist.add_task(String::from("one #backend")).unwrap();
+    tasklist.add_task(String::from("two")).unwrap();
+    tasklist.add_task(String::from("three")).unwrap();
+    file.save_tasklist(&mut tasklist).unwrap();
+    let id = file.records[0].id.clone();
+
+    let mut file = File::from(&path);
+    let mut tasklist = file.load_tasklist().unwrap();
+    assert_eq!(3, tasklist.get_tasks(&GetTasksFilterOption::All).len());
+    tasklist.update_task(TaskUpdateAction::Toggle, "one #backend");
+    tasklist.update_task(TaskUpdateAction::Edit("second"), "two");
+    tasklist.update_task(TaskUpdateAction::Delete, "three");
+    file.save_tasklist(&mut tasklist).unwrap();
+
+    let mut file = File::from(&path);
+    file.read_file().unwrap();
+    let records = &file.records;
+    assert_eq!(2, records.len());
+    assert_eq!(id, records[0].id);
+    assert_eq!(TaskStatus::Done, records[0].status);
+    assert!(records[0].completed.is_some());
+    assert_eq!(vec!["backend"], records[0].tags);
+    assert_eq!("second", records[1].description);
+    assert_eq!(None, records[1].completed);
+
+    fs::remove_dir_all(&dir).unwrap();
+  }
+
+  #[test]
+  fn test_reads_version_1() {
+    let dir = std::env::temp_dir().join(format!("taskmaster-json-v1-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("tasks.json").to_string_lossy().to_string();
+    let record = |description: &str, is_completed: bool| {
+      format!(
+        r#"{{"id":"{description}","description":"{description}","is_completed":{is_completed},
+            "created":"2026-10-01T09:00:00+00:00"}}"#
+      )
+    };
+    let contents = format!(
+      r#"{{"version":1,"tasks":[{},{}]}}"#,
+      record("one", true),
+      record("two", false)
+    );
+    fs::write(&path, contents).unwrap();
+
+    let mut file = File::from(&path);
+    let tasks = file
+      .load_tasklist()
+      .unwrap()
+      .get_tasks(&GetTasksFilterOption::All);
+    assert_eq!(TaskStatus::Done, tasks[0].status);
+    assert_eq!(TaskStatus::Todo, tasks[1].status);
+
+    fs::remove_dir_all(&dir).unwrap();
+  }
+}
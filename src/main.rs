@@ -1,13 +1,28 @@
-use anyhow::Result;
+use anyhow::{Result, anyhow};
+use chrono::{Datelike, Local, NaiveDate};
 use clap::Parser;
 use flexi_logger::{FileSpec, Logger, WriteMode};
 use log::info;
+use std::path::Path;
+use std::{fs, io};
 
 mod cli;
+mod config;
+mod directory;
+mod editor;
+mod exchange;
+mod journal;
+mod json;
 mod markdown;
+mod notes;
+mod search;
+mod sqlite;
+mod storage;
 mod tasks;
+mod timer;
+mod todotxt;
 
-const TASKS_FILE: &str = "tasks.md";
+const CONFIG_FILE: &str = "taskmaster.toml";
 
 fn main() -> Result<()> {
   let _logger = Logger::try_with_env_or_str("info") // use RUST_LOG=debug for debug level
@@ -22,15 +37,202 @@ fn main() -> Result<()> {
 
   let cli = cli::Cli::parse();
 
-  let md_file = markdown::File::from(TASKS_FILE);
-  let mut task_io = tasks::TaskController::new(md_file)?;
+  let config = config::Config::load(CONFIG_FILE)?;
+
+  let store = match (&cli.store, &config.store) {
+    (Some(store), _) => store.clone(),
+    (None, Some(store)) => store.parse()?,
+    (None, None) => storage::StoreSpec::default(),
+  };
 
   match &cli.command {
-    cli::Command::Tasks(task_cmd) => match &task_cmd.command {
-      cli::TaskCommand::Add { description } => task_io.add(description.to_owned())?,
-      cli::TaskCommand::List => task_io.list()?,
+    cli::Command::Tasks(task_cmd) => {
+      let note_dirs = notes::dirs(&config, &store);
+      let mut task_io =
+        tasks::TaskController::new(storage::open(&store, &config), config, note_dirs)?;
+
+      match &task_cmd.command {
+        cli::TaskCommand::Add {
+          description,
+          dry_run,
+        } => task_io.add(description, *dry_run)?,
+        cli::TaskCommand::Edit {
+          task,
+          description: Some(description),
+          ..
+        } => task_io.edit(task, description)?,
+        cli::TaskCommand::Edit { task, .. } => task_io.edit_in_editor(task)?,
+        cli::TaskCommand::List {
+          show,
+          filter,
+          format,
+        } => {
+          let list_option = match filter {
+            Some(filter) => tasks::GetTasksFilterOption::Query(task_io.resolve_filter(filter)?),
+            None => (*show).into(),
+          };
+          task_io.list(list_option, *format)?
+        }
+      }
+    }
+    cli::Command::Timer(timer_cmd) => {
+      let mut timer = timer::TimerController::new(storage::open(&store, &config), config)?;
+
+      match &timer_cmd.command {
+        cli::TimerCommand::Pomodoro { task } => timer.pomodoro(task)?,
+        cli::TimerCommand::Start { task } => timer.start(task)?,
+        cli::TimerCommand::Stop => timer.stop()?,
+        cli::TimerCommand::Log {
+          task,
+          duration: Some(duration),
+          at,
+        } => timer.log(task, duration, at.as_deref())?,
+        cli::TimerCommand::Log {
+          task,
+          duration: None,
+          ..
+        } => timer.entries(task)?,
+        cli::TimerCommand::Edit {
+          task,
+          entry,
+          at,
+          duration,
+        } => timer.edit(task, *entry, at.as_deref(), duration.as_deref())?,
+        cli::TimerCommand::Rm { task, entry } => timer.remove(task, *entry)?,
+      }
+    }
+    cli::Command::Status { task, all } => {
+      let mut storage = storage::open(&store, &config);
+      if task.is_some() || *all {
+        let entries = storage.load_time_entries()?;
+        timer::write_status(
+          &mut io::stdout().lock(),
+          &storage.load_tasklist()?,
+          &entries,
+          task.as_deref(),
+          Local::now(),
+        )?;
+      } else {
+        // the one line is meant for prompts, which shouldn't fail on stores
+        // that don't keep time
+        let entries = storage.load_time_entries().unwrap_or_default();
+        timer::write_running(&mut io::stdout().lock(), &entries, Local::now())?;
+      }
+    }
+    cli::Command::Note(note_cmd) => {
+      let (links, files, tasks) = notes::links::collect(
+        &mut storage::open(&store, &config),
+        &notes::dirs(&config, &store),
+      )?;
+      let targets = notes::links::Targets::new(&files, &tasks);
+
+      match &note_cmd.command {
+        cli::NoteCommand::Backlinks { note } => {
+          notes::links::write_backlinks(&mut io::stdout().lock(), &links, &targets, note)?
+        }
+        cli::NoteCommand::Check => notes::links::check(&mut io::stdout().lock(), &links, &targets)?,
+      }
+    }
+    cli::Command::Search {
+      query,
+      regex,
+      ignore_case,
+      show,
+      format,
+    } => {
+      let query = search::Query::new(query, *regex, *ignore_case)?;
+      let results = search::search(
+        &mut storage::open(&store, &config),
+        &notes::dirs(&config, &store),
+        &query,
+        &(*show).into(),
+      )?;
+      search::write(
+        &mut io::stdout().lock(),
+        &results,
+        matches!(format, cli::SearchFormat::Json),
+      )?;
+    }
+    cli::Command::Journal { date, summary } => {
+      let date = match date {
+        Some(value) => tasks::filter::DateExpr::parse(value)?
+          .resolve(Local::now().date_naive())
+          .ok_or_else(|| anyhow!("Invalid date \"{}\"", value))?,
+        None => Local::now().date_naive(),
+      };
+      // a directory store reads the notes inside it as it does any other file
+      let in_store =
+        |dir: &Path| store.kind == storage::StoreKind::Directory && dir.starts_with(&store.path);
+      let dir = notes::journal_dir(&config, &store);
+      let pick_up = !in_store(&dir);
+
+      let mut journal =
+        journal::JournalController::new(storage::open(&store, &config), config, dir, pick_up)?;
+      journal.open(date, *summary)?;
+    }
+    cli::Command::Report(report_cmd) => match &report_cmd.command {
+      cli::ReportCommand::Time {
+        from,
+        to,
+        group_by,
+        format,
+      } => {
+        let today = Local::now().date_naive();
+        let resolve = |value: &Option<String>, default: NaiveDate| -> Result<NaiveDate> {
+          match value {
+            Some(value) => tasks::filter::DateExpr::parse(value)?
+              .resolve(today)
+              .ok_or_else(|| anyhow!("Invalid date \"{}\"", value)),
+            None => Ok(default),
+          }
+        };
+        let from = resolve(from, today.with_day(1).unwrap())?;
+        let to = resolve(to, today)?;
+
+        let entries = storage::open(&store, &config).load_time_entries()?;
+        let report = timer::TimeReport::build(
+          &entries,
+          from,
+          to,
+          (*group_by).into(),
+          &config.report,
+          Local::now(),
+        );
+        report.write(&mut io::stdout().lock(), *format)?;
+      }
+      cli::ReportCommand::Estimates { format } => {
+        let mut storage = storage::open(&store, &config);
+        let tasks = storage
+          .load_tasklist()?
+          .get_tasks(&tasks::GetTasksFilterOption::All);
+        let report =
+          timer::EstimateReport::build(&tasks, &storage.load_time_entries()?, Local::now());
+        report.write(&mut io::stdout().lock(), *format)?;
+      }
     },
-    cli::Command::Status => unimplemented!(),
+    cli::Command::Migrate { from, to } => {
+      let count = storage::migrate(from, to, &config)?;
+      println!("Migrated {} tasks from {} to {}", count, from.path, to.path);
+    }
+    cli::Command::Import { format, file } => {
+      let input = fs::read_to_string(file)?;
+      let report = exchange::import(
+        &mut storage::open(&store, &config),
+        (*format).into(),
+        &input,
+      )?;
+      println!("{}", report);
+    }
+    cli::Command::Export { format, output } => {
+      let mut store = storage::open(&store, &config);
+      match output {
+        Some(path) => {
+          let mut out = fs::File::create(path)?;
+          exchange::export(&mut store, (*format).into(), &mut out)?
+        }
+        None => exchange::export(&mut store, (*format).into(), &mut io::stdout().lock())?,
+      }
+    }
   }
   Ok(())
 }
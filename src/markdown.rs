@@ -1,50 +1,201 @@
-use anyhow::Result;
-use std::fs::OpenOptions;
-use std::io::{Read, Write};
+use anyhow::{Result, anyhow};
+use log::{debug, info};
+use regex::Regex;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fs::{self, OpenOptions};
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::io::{ErrorKind, Read};
+use std::path::PathBuf;
+use std::sync::LazyLock;
+use std::time::SystemTime;
 
-use crate::tasks::{TaskList, TaskListPersist};
+use crate::storage;
+use crate::tasks::tasklist::CodeFence;
+use crate::tasks::{MergeConflict, MergeResolution, TaskChange, TaskList, TaskListPersist};
+use crate::timer::{TaskTime, TimeEntry};
+
+pub type ConflictResolver = fn(&MergeConflict) -> Result<MergeResolution>;
+
+static HEADING_RE: LazyLock<Regex> =
+  LazyLock::new(|| Regex::new(r"^#{1,6}\s+(.+?)[\s#]*$").unwrap());
+/// A time entry listed under its task, e.g. `  - ⏱ 2026-10-17 14:00-15:30`
+static TIME_RE: LazyLock<Regex> =
+  LazyLock::new(|| Regex::new(r"^(\s*)[-*+]\s+⏱\u{fe0f}?\s+(.+)$").unwrap());
+
+/// What the file looked like when it was last read or written
+#[derive(Debug, PartialEq)]
+struct Stamp {
+  modified: Option<SystemTime>,
+  hash: u64,
+}
 
 pub struct File<'a> {
-  pub path: &'a str,
+  pub path: Cow<'a, str>,
   lines: Vec<String>,
+  line_ending: &'static str,
+  trailing_newline: bool,
+  backup: bool,
+  lock: Option<fs::File>,
+  stamp: Option<Stamp>,
+  resolve_conflict: Option<ConflictResolver>,
 }
 
 impl<'a> File<'a> {
-  pub fn from(path: &'a str) -> File<'a> {
+  pub fn from(path: impl Into<Cow<'a, str>>) -> File<'a> {
     File {
-      path,
+      path: path.into(),
       lines: Vec::new(),
+      line_ending: "\n",
+      trailing_newline: true,
+      backup: false,
+      lock: None,
+      stamp: None,
+      resolve_conflict: None,
     }
   }
 
+  /// Keep the previous version of the file as `<path>.bak` on every save
+  pub fn with_backup(mut self, backup: bool) -> File<'a> {
+    self.backup = backup;
+    self
+  }
+
+  /// Called for each task that was changed both here and in the file
+  /// since it was loaded. Without one, saving fails on a conflict.
+  pub fn with_conflict_resolver(mut self, resolver: ConflictResolver) -> File<'a> {
+    self.resolve_conflict = Some(resolver);
+    self
+  }
+
   pub fn write_file(&self) -> Result<()> {
-    let mut file = OpenOptions::new()
-      .write(true)
-      .truncate(true)
-      .create(true)
-      .open(&self.path)?;
+    storage::write_atomic(&self.path, &self.contents(), self.backup)
+  }
+
+  /// Writes the file and remembers what it looks like now, so that the
+  /// write isn't taken for a change made outside the tool
+  fn write_and_stamp(&mut self) -> Result<()> {
+    self.write_file()?;
 
-    file.write_all(self.lines.join("\n").as_bytes())?;
+    let contents = self.contents();
+    self.stamp = Some(Stamp {
+      modified: fs::metadata(&*self.path)?.modified().ok(),
+      hash: hash_contents(&contents),
+    });
 
     Ok(())
   }
 
   pub fn read_file(&mut self) -> Result<()> {
-    let mut file = OpenOptions::new().read(true).open(&self.path)?;
+    let mut file = match OpenOptions::new().read(true).open(&*self.path) {
+      Ok(file) => file,
+      // created on the first save
+      Err(err) if err.kind() == ErrorKind::NotFound => {
+        self.set_contents("");
+        self.stamp = None;
+        return Ok(());
+      }
+      Err(err) => return Err(err.into()),
+    };
 
     let mut contents = String::new();
     file.read_to_string(&mut contents)?;
 
-    self.lines = contents
-      .split("\n")
-      .map(|line| line.trim().to_string())
-      .collect();
+    self.set_contents(&contents);
+    self.stamp = Some(Stamp {
+      modified: file.metadata()?.modified().ok(),
+      hash: hash_contents(&contents),
+    });
 
     Ok(())
   }
+
+  fn has_changed_on_disk(&self) -> Result<bool> {
+    let Some(stamp) = &self.stamp else {
+      return Ok(false);
+    };
+
+    let modified = match fs::metadata(&*self.path) {
+      Ok(metadata) => metadata.modified().ok(),
+      // removed since it was read
+      Err(_) => return Ok(true),
+    };
+    if modified == stamp.modified {
+      return Ok(false);
+    }
+
+    // the mtime alone changes on a touch or a save without edits
+    let contents = fs::read_to_string(&*self.path)?;
+    Ok(hash_contents(&contents) != stamp.hash)
+  }
+
+  fn set_contents(&mut self, contents: &str) {
+    // tasks added to an empty file get a line each
+    if contents.is_empty() {
+      self.lines.clear();
+      self.line_ending = "\n";
+      self.trailing_newline = true;
+      return;
+    }
+
+    let mut lines: Vec<&str> = contents.split('\n').collect();
+
+    self.trailing_newline = lines.len() > 1 && lines.last() == Some(&"");
+    if self.trailing_newline {
+      lines.pop();
+    }
+
+    // only treat the file as CRLF if every line break is one, otherwise
+    // any stray \r is kept as part of its line
+    let terminated = if self.trailing_newline {
+      lines.len()
+    } else {
+      lines.len() - 1
+    };
+    let is_crlf = terminated > 0 && lines[..terminated].iter().all(|line| line.ends_with('\r'));
+
+    self.line_ending = if is_crlf { "\r\n" } else { "\n" };
+    self.lines = lines
+      .into_iter()
+      .enumerate()
+      .map(|(i, line)| match line.strip_suffix('\r') {
+        Some(stripped) if is_crlf && i < terminated => stripped.to_string(),
+        _ => line.to_string(),
+      })
+      .collect();
+  }
+
+  /// The line number, counting from 1, of each task as last read
+  pub fn task_lines(&self) -> HashMap<String, usize> {
+    TaskList::md_tasks(&self.lines)
+      .map(|(i, (_, _, _, description))| (description.trim().to_string(), i + 1))
+      .collect()
+  }
+
+  fn contents(&self) -> String {
+    let mut contents = self.lines.join(self.line_ending);
+    if self.trailing_newline && !self.lines.is_empty() {
+      contents.push_str(self.line_ending);
+    }
+
+    contents
+  }
 }
 
 impl<'a> TaskListPersist for File<'a> {
+  fn lock(&mut self) -> Result<()> {
+    self.lock = Some(storage::lock(&self.path)?);
+    Ok(())
+  }
+
+  fn unlock(&mut self) {
+    self.lock = None;
+  }
+
+  fn watch_path(&self) -> Option<PathBuf> {
+    Some(PathBuf::from(&*self.path))
+  }
+
   fn load_tasklist(&mut self) -> Result<TaskList> {
     self.read_file()?;
     let tasklist = TaskList::from_markdown(&self.lines)?;
@@ -53,7 +204,534 @@ impl<'a> TaskListPersist for File<'a> {
   }
 
   fn save_tasklist(&mut self, tasklist: &mut TaskList) -> Result<()> {
+    if self.has_changed_on_disk()? {
+      info!("{} changed on disk; merging", self.path);
+      if fs::exists(&*self.path)? {
+        self.read_file()?;
+      } else {
+        self.lines.clear();
+      }
+
+      for conflict in tasklist.merge_conflicts(&self.lines) {
+        let resolution = match self.resolve_conflict {
+          Some(resolve) => resolve(&conflict)?,
+          None => {
+            return Err(anyhow!(
+              "\"{}\" was changed in {} since it was loaded",
+              conflict.key,
+              self.path
+            ));
+          }
+        };
+        debug!("resolved {:?} with {:?}", conflict, resolution);
+        tasklist.resolve_conflict(&conflict, resolution);
+      }
+    }
+
+    // notes and time entries of deleted tasks would otherwise end up under
+    // the task above
+    for change in tasklist.changes() {
+      if let TaskChange::Deleted(description) = change {
+        let _ = set_time_entries(&mut self.lines, &description, &[]);
+        let _ = set_notes(&mut self.lines, &description, &[]);
+      }
+    }
+
     tasklist.save_to_markdown(&mut self.lines)?;
-    self.write_file()
+    self.write_and_stamp()
+  }
+
+  fn load_time_entries(&mut self) -> Result<Vec<TaskTime>> {
+    self.read_file()?;
+    Ok(time_entries(&self.lines))
+  }
+
+  fn save_time_entries(&mut self, description: &str, entries: &[TimeEntry]) -> Result<()> {
+    self.read_file()?;
+    set_time_entries(&mut self.lines, description, entries)?;
+    self.write_and_stamp()
+  }
+
+  fn load_notes(&mut self, description: &str) -> Result<Vec<String>> {
+    self.read_file()?;
+    notes(&self.lines, description)
+  }
+
+  fn save_notes(&mut self, description: &str, notes: &[String]) -> Result<()> {
+    self.read_file()?;
+    set_notes(&mut self.lines, description, notes)?;
+    self.write_and_stamp()
+  }
+
+  fn load_all_notes(&mut self) -> Result<HashMap<String, Vec<String>>> {
+    self.read_file()?;
+    Ok(all_notes(&self.lines))
+  }
+}
+
+/// The time entries in `lines`, each belonging to the task it's indented under
+pub fn time_entries(lines: &[String]) -> Vec<TaskTime> {
+  let mut fence = CodeFence::default();
+  let mut section: Option<String> = None;
+  // the indentation and description of the task entries are listed under
+  let mut task: Option<(usize, String)> = None;
+  let mut entries = Vec::new();
+
+  for line in lines {
+    if fence.is_code(line) {
+      continue;
+    }
+    if let Some(caps) = HEADING_RE.captures(line) {
+      section = Some(caps[1].to_string());
+      task = None;
+      continue;
+    }
+    if let Some((start, _, _, description)) = TaskList::get_md_captures(line) {
+      task = Some((start, description.trim().to_string()));
+      continue;
+    }
+
+    let indent = indent_of(line);
+    match (&task, TIME_RE.captures(line)) {
+      (Some((start, description)), Some(caps)) if indent > *start => {
+        match TimeEntry::parse(&caps[2]) {
+          Ok(entry) => entries.push(TaskTime {
+            task: description.clone(),
+            section: section.clone(),
+            entry,
+          }),
+          Err(err) => info!("ignoring time entry under \"{}\": {}", description, err),
+        }
+      }
+      (Some((start, _)), _) if indent <= *start && !line.trim().is_empty() => task = None,
+      _ => {}
+    }
+  }
+
+  entries
+}
+
+/// Replaces the time entries listed under the task with `description`
+pub fn set_time_entries(
+  lines: &mut Vec<String>,
+  description: &str,
+  entries: &[TimeEntry],
+) -> Result<()> {
+  let (line, start, bullet) = find_task(lines, description)?;
+  let end = block_end(lines, line, start);
+
+  // new entries go where the old ones were, or after everything else
+  let mut indent = " ".repeat(start + bullet + 1);
+  let mut at = end;
+  for i in (line + 1..end).rev() {
+    if let Some(caps) = TIME_RE.captures(&lines[i]) {
+      indent = caps[1].to_string();
+      at = i;
+      lines.remove(i);
+    }
+  }
+
+  let entry_lines = entries
+    .iter()
+    .map(|entry| format!("{}- ⏱ {}", indent, entry));
+  lines.splice(at..at, entry_lines);
+
+  Ok(())
+}
+
+/// The lines indented under the task with `description` other than its time
+/// entries, unindented
+pub fn notes(lines: &[String], description: &str) -> Result<Vec<String>> {
+  let (line, start, _) = find_task(lines, description)?;
+  Ok(notes_at(lines, line, start))
+}
+
+/// The notes under every task in `lines` that has some, by description
+pub fn all_notes(lines: &[String]) -> HashMap<String, Vec<String>> {
+  let mut all = HashMap::new();
+  for (line, (start, _, _, description)) in TaskList::md_tasks(lines) {
+    let notes = notes_at(lines, line, start);
+    // the first task with a description is the one that's listed
+    if !notes.is_empty() {
+      all.entry(description.trim().to_string()).or_insert(notes);
+    }
+  }
+  all
+}
+
+fn notes_at(lines: &[String], line: usize, start: usize) -> Vec<String> {
+  let end = block_end(lines, line, start);
+  let notes: Vec<&String> = lines[line + 1..end]
+    .iter()
+    .filter(|text| !TIME_RE.is_match(text))
+    .collect();
+
+  // other whitespace is kept as text, so cutting it never splits a character
+  let indent = notes
+    .iter()
+    .filter(|text| !text.trim().is_empty())
+    .map(|text| text.len() - text.trim_start_matches([' ', '\t']).len())
+    .min()
+    .unwrap_or_default();
+  notes
+    .into_iter()
+    .map(|text| text.get(indent..).unwrap_or_default().to_string())
+    .collect()
+}
+
+/// Replaces the notes under the task with `description`, keeping its time
+/// entries
+pub fn set_notes(lines: &mut Vec<String>, description: &str, notes: &[String]) -> Result<()> {
+  let (line, start, bullet) = find_task(lines, description)?;
+  let end = block_end(lines, line, start);
+
+  // indented like the old notes, or else like the time entries
+  let mut indent = None;
+  let mut time_indent = None;
+  for i in (line + 1..end).rev() {
+    if let Some(caps) = TIME_RE.captures(&lines[i]) {
+      time_indent = Some(caps[1].to_string());
+      continue;
+    }
+    if !lines[i].trim().is_empty() {
+      let width = indent_of(&lines[i]);
+      if indent
+        .as_ref()
+        .is_none_or(|indent: &String| width < indent.len())
+      {
+        indent = Some(" ".repeat(width));
+      }
+    }
+    lines.remove(i);
+  }
+  let indent = indent
+    .or(time_indent)
+    .unwrap_or_else(|| " ".repeat(start + bullet + 1));
+
+  let note_lines = notes.iter().map(|note| {
+    if note.trim().is_empty() {
+      String::new()
+    } else {
+      format!("{}{}", indent, note)
+    }
+  });
+  lines.splice(line + 1..line + 1, note_lines);
+
+  Ok(())
+}
+
+/// The line of the task with `description`, where its bullet starts and how
+/// long the bullet is
+fn find_task(lines: &[String], description: &str) -> Result<(usize, usize, usize)> {
+  TaskList::md_tasks(lines)
+    .find(|(_, (_, _, _, d))| d.trim() == description)
+    .map(|(i, (start, bullet, _, _))| (i, start, bullet.len()))
+    .ok_or_else(|| anyhow!("No task \"{}\"", description))
+}
+
+/// The end of the lines indented under the task at `line`, such as notes
+/// and time entries, up to the next task
+pub fn block_end(lines: &[String], line: usize, indent: usize) -> usize {
+  let mut end = line + 1;
+  for (i, text) in lines.iter().enumerate().skip(line + 1) {
+    if text.trim().is_empty() {
+      continue;
+    }
+    if indent_of(text) <= indent || TaskList::get_md_captures(text).is_some() {
+      break;
+    }
+    end = i + 1;
+  }
+  end
+}
+
+fn indent_of(line: &str) -> usize {
+  line.len() - line.trim_start().len()
+}
+
+fn hash_contents(contents: &str) -> u64 {
+  let mut hasher = DefaultHasher::new();
+  contents.hash(&mut hasher);
+  hasher.finish()
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use proptest::prelude::*;
+
+  fn round_trip(contents: &str) -> String {
+    let mut file = File::from("unused.md");
+    file.set_contents(contents);
+
+    let mut tasklist = TaskList::from_markdown(&file.lines).unwrap();
+    tasklist.save_to_markdown(&mut file.lines).unwrap();
+
+    file.contents()
+  }
+
+  #[test]
+  fn test_keeps_formatting() {
+    let contents = "# Tasks\r\n\r\n  - [ ] indented  \r\n```\r\n  code  \r\n```\r\n- [x] done\r\n";
+    assert_eq!(contents, round_trip(contents));
+
+    let contents = "- [ ] no trailing newline\n\tmixed\r\nendings";
+    assert_eq!(contents, round_trip(contents));
+  }
+
+  #[test]
+  fn test_keeps_line_ending_for_changes() {
+    let mut file = File::from("unused.md");
+    file.set_contents("  - [ ] one\r\n- [ ] two\r\n");
+
+    let mut tasklist = TaskList::from_markdown(&file.lines).unwrap();
+    tasklist.update_task(crate::tasks::TaskUpdateAction::Toggle, "one");
+    tasklist.add_task(String::from("three")).unwrap();
+    tasklist.save_to_markdown(&mut file.lines).unwrap();
+
+    assert_eq!(
+      "  - [x] one\r\n- [ ] two\r\n- [ ] three\r\n",
+      file.contents()
+    );
+  }
+
+  #[test]
+  fn test_keeps_obsidian_fields() {
+    let mut file = File::from("unused.md");
+    file.set_contents(
+      "- [ ] Water plants ⏫ 🔁 every week 📅 2026-10-19\n- [x] Pay rent ✅ 2026-10-02\n",
+    );
+
+    let mut tasklist = TaskList::from_markdown(&file.lines).unwrap();
+    tasklist.update_task(
+      crate::tasks::TaskUpdateAction::Toggle,
+      "Water plants ⏫ 🔁 every week 📅 2026-10-19",
+    );
+    tasklist.update_task(
+      crate::tasks::TaskUpdateAction::Toggle,
+      "Pay rent ✅ 2026-10-02",
+    );
+    tasklist.save_to_markdown(&mut file.lines).unwrap();
+
+    let today = chrono::Local::now().date_naive().format("%Y-%m-%d");
+    assert_eq!(
+      format!("- [x] Water plants ⏫ 🔁 every week 📅 2026-10-19 ✅ {today}\n- [ ] Pay rent\n"),
+      file.contents()
+    );
+  }
+
+  #[test]
+  fn test_write_file_replaces_and_backs_up() {
+    let dir = std::env::temp_dir().join(format!("taskmaster-md-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("tasks.md").to_string_lossy().to_string();
+    fs::write(&path, "- [ ] old\n").unwrap();
+
+    let mut file = File::from(&path).with_backup(true);
+    file.lock().unwrap();
+    file.read_file().unwrap();
+    file.lines = vec![String::from("- [ ] new")];
+    file.write_file().unwrap();
+
+    assert_eq!("- [ ] new\n", fs::read_to_string(&path).unwrap());
+    assert_eq!(
+      "- [ ] old\n",
+      fs::read_to_string(format!("{}.bak", path)).unwrap()
+    );
+    assert!(!fs::exists(format!("{}.tmp", path)).unwrap());
+
+    fs::remove_dir_all(&dir).unwrap();
+  }
+
+  #[test]
+  fn test_save_merges_external_changes() {
+    let dir = std::env::temp_dir().join(format!("taskmaster-merge-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("tasks.md").to_string_lossy().to_string();
+    fs::write(&path, "- [ ] one\n- [ ] two\n").unwrap();
+
+    let mut file = File::from(&path);
+    let mut tasklist = file.load_tasklist().unwrap();
+    tasklist.update_task(crate::tasks::TaskUpdateAction::Toggle, "one");
+
+    fs::write(&path, "- [ ] one\n- [ ] two\n- [ ] from the editor\n").unwrap();
+    file.save_tasklist(&mut tasklist).unwrap();
+
+    assert_eq!(
+      "- [x] one\n- [ ] two\n- [ ] from the editor\n",
+      fs::read_to_string(&path).unwrap()
+    );
+
+    fs::remove_dir_all(&dir).unwrap();
+  }
+
+  #[test]
+  fn test_delete_removes_notes() {
+    let dir = std::env::temp_dir().join(format!("taskmaster-delete-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("tasks.md").to_string_lossy().to_string();
+    fs::write(
+      &path,
+      "- [ ] one\n- [ ] two\n  see the draft\n  - ⏱ 2026-10-17 09:00-10:30\n  - [ ] subtask\n",
+    )
+    .unwrap();
+
+    let mut file = File::from(&path);
+    let mut tasklist = file.load_tasklist().unwrap();
+    tasklist.update_task(crate::tasks::TaskUpdateAction::Delete, "two");
+    file.save_tasklist(&mut tasklist).unwrap();
+
+    assert_eq!(
+      "- [ ] one\n  - [ ] subtask\n",
+      fs::read_to_string(&path).unwrap()
+    );
+
+    fs::remove_dir_all(&dir).unwrap();
+  }
+
+  #[test]
+  fn test_time_entries_under_tasks() {
+    let lines: Vec<String> = [
+      "# Work",
+      "- [ ] write spec",
+      "  - ⏱ 2026-10-17 09:00-10:30",
+      "  - a note",
+      "  - ⏱ 2026-10-17 14:00-",
+      "- ⏱ 2026-10-17 16:00-17:00",
+      "## Home",
+      "* [x] fix bike",
+      "    - ⏱ 2026-10-18 10:00-10:20",
+      "```",
+      "    - ⏱ 2026-10-18 11:00-12:00",
+      "```",
+    ]
+    .map(String::from)
+    .to_vec();
+
+    let entries = time_entries(&lines);
+    let summary: Vec<(&str, Option<&str>, String)> = entries
+      .iter()
+      .map(|time| {
+        (
+          time.task.as_str(),
+          time.section.as_deref(),
+          time.entry.to_string(),
+        )
+      })
+      .collect();
+    assert_eq!(
+      vec![
+        (
+          "write spec",
+          Some("Work"),
+          String::from("2026-10-17 09:00-10:30")
+        ),
+        (
+          "write spec",
+          Some("Work"),
+          String::from("2026-10-17 14:00-")
+        ),
+        (
+          "fix bike",
+          Some("Home"),
+          String::from("2026-10-18 10:00-10:20")
+        ),
+      ],
+      summary
+    );
+  }
+
+  #[test]
+  fn test_set_time_entries() {
+    let mut lines: Vec<String> = [
+      "1. [ ] write spec",
+      "   - ⏱ 2026-10-17 09:00-10:30",
+      "   - a note",
+      "   - [ ] subtask",
+      "     - ⏱ 2026-10-17 11:00-11:30",
+      "2. [ ] review",
+    ]
+    .map(String::from)
+    .to_vec();
+
+    let entries = [TimeEntry::parse("2026-10-18 14:00-14:25 🍅").unwrap()];
+    set_time_entries(&mut lines, "write spec", &entries).unwrap();
+    set_time_entries(&mut lines, "review", &entries).unwrap();
+    assert!(set_time_entries(&mut lines, "missing", &[]).is_err());
+
+    let expected: Vec<String> = [
+      "1. [ ] write spec",
+      "   - ⏱ 2026-10-18 14:00-14:25 🍅",
+      "   - a note",
+      "   - [ ] subtask",
+      "     - ⏱ 2026-10-17 11:00-11:30",
+      "2. [ ] review",
+      "   - ⏱ 2026-10-18 14:00-14:25 🍅",
+    ]
+    .map(String::from)
+    .to_vec();
+    assert_eq!(expected, lines);
+    assert_eq!(3, time_entries(&lines).len());
+  }
+
+  #[test]
+  fn test_notes() {
+    let mut lines: Vec<String> = [
+      "- [ ] write spec",
+      "  - ⏱ 2026-10-17 09:00-10:30",
+      "  see the draft",
+      "    - indented",
+      "",
+      "  - [ ] subtask",
+      "- [ ] review",
+    ]
+    .map(String::from)
+    .to_vec();
+
+    assert_eq!(
+      vec!["see the draft", "  - indented"],
+      notes(&lines, "write spec").unwrap()
+    );
+    assert!(notes(&lines, "review").unwrap().is_empty());
+    assert_eq!(
+      HashMap::from([(
+        String::from("write spec"),
+        notes(&lines, "write spec").unwrap()
+      )]),
+      all_notes(&lines)
+    );
+
+    let new = ["new note".to_string(), String::new(), "- more".to_string()];
+    set_notes(&mut lines, "write spec", &new).unwrap();
+    set_notes(&mut lines, "review", &new[..1]).unwrap();
+    set_notes(&mut lines, "subtask", &[]).unwrap();
+
+    let expected: Vec<String> = [
+      "- [ ] write spec",
+      "  new note",
+      "",
+      "  - more",
+      "  - ⏱ 2026-10-17 09:00-10:30",
+      "",
+      "  - [ ] subtask",
+      "- [ ] review",
+      "  new note",
+    ]
+    .map(String::from)
+    .to_vec();
+    assert_eq!(expected, lines);
+    assert_eq!(1, time_entries(&lines).len());
+  }
+
+  proptest! {
+    #[test]
+    fn test_unchanged_round_trip_is_lossless(contents in "(( {0,3}- \\[[ x]\\] )?[a-z \t`#-]{0,12}\r?\n?){0,8}") {
+      prop_assert_eq!(&contents, &round_trip(&contents));
+    }
+
+    #[test]
+    fn test_unchanged_round_trip_of_any_text(contents in "\\PC*(\r?\n\\PC*){0,5}") {
+      prop_assert_eq!(&contents, &round_trip(&contents));
+    }
   }
 }
@@ -0,0 +1,266 @@
+use anyhow::{Result, anyhow};
+use regex::Regex;
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::LazyLock;
+
+use crate::notes;
+use crate::tasks::{GetTasksFilterOption, Task, TaskListPersist, TaskMeta};
+
+/// A wiki link, e.g. `[[spec]]`, `[[spec#Goals]]` or `[[spec|the spec]]`,
+/// capturing the name of what it links to
+static LINK_RE: LazyLock<Regex> =
+  LazyLock::new(|| Regex::new(r"\[\[([^\[\]|#]+)(?:#[^\[\]|]*)?(?:\|[^\[\]]*)?\]\]").unwrap());
+
+/// The names `text` links to, in order
+pub fn names(text: &str) -> Vec<String> {
+  LINK_RE
+    .captures_iter(text)
+    .map(|caps| caps[1].trim().to_string())
+    .filter(|name| !name.is_empty())
+    .collect()
+}
+
+/// What a link name refers to
+#[derive(Clone, Debug, PartialEq)]
+pub enum Target {
+  Note(PathBuf),
+  /// By description
+  Task(String),
+}
+
+/// Where a link was found
+#[derive(Debug, PartialEq)]
+pub enum Source {
+  /// The task's description or its notes
+  Task(Task),
+  /// A note and the line in it, counted from 1
+  Note(PathBuf, usize),
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Link {
+  pub source: Source,
+  pub name: String,
+}
+
+/// The notes and tasks links can point to. A note is linked to by its file
+/// name without `.md`, or by its path when that's ambiguous, and a task by
+/// its description or just its title. Notes come first, as in Obsidian.
+pub struct Targets<'t> {
+  notes: &'t [PathBuf],
+  tasks: &'t [Task],
+}
+
+impl<'t> Targets<'t> {
+  pub fn new(notes: &'t [PathBuf], tasks: &'t [Task]) -> Targets<'t> {
+    Targets { notes, tasks }
+  }
+
+  pub fn resolve(&self, name: &str) -> Option<Target> {
+    let wanted = name.trim().trim_end_matches(".md").to_lowercase();
+
+    let note = self.notes.iter().find(|path| {
+      let stem = path.with_extension("");
+      let stem = stem.to_string_lossy().to_lowercase().replace('\\', "/");
+      stem == wanted || stem.ends_with(&format!("/{}", wanted))
+    });
+    if let Some(path) = note {
+      return Some(Target::Note(path.clone()));
+    }
+
+    self
+      .tasks
+      .iter()
+      .find(|task| task.description == name.trim())
+      .or_else(|| {
+        self
+          .tasks
+          .iter()
+          .find(|task| TaskMeta::parse(&task.description).title.to_lowercase() == wanted)
+      })
+      .map(|task| Target::Task(task.description.clone()))
+  }
+}
+
+/// The links in the tasks, their notes and the notes under `note_dirs`,
+/// along with the note files and tasks they can point to
+pub fn collect<S: TaskListPersist>(
+  storage: &mut S,
+  note_dirs: &[PathBuf],
+) -> Result<(Vec<Link>, Vec<PathBuf>, Vec<Task>)> {
+  let tasks = storage
+    .load_tasklist()?
+    .get_tasks(&GetTasksFilterOption::All);
+  // not every store keeps notes
+  let notes = storage.load_all_notes().unwrap_or_default();
+  let mut links = Vec::new();
+
+  for task in &tasks {
+    let notes = notes.get(&task.description).map(Vec::as_slice);
+    let text = [task.description.as_str()]
+      .into_iter()
+      .chain(notes.unwrap_or_default().iter().map(String::as_str));
+    for name in text.flat_map(names) {
+      links.push(Link {
+        source: Source::Task(task.clone()),
+        name,
+      });
+    }
+  }
+
+  let files = notes::files(note_dirs)?;
+  for path in &files {
+    let lines: Vec<String> = fs::read_to_string(path)?
+      .lines()
+      .map(String::from)
+      .collect();
+    // tasks in notes are read from the store above
+    for (i, line) in notes::text_lines(&lines) {
+      for name in names(line) {
+        links.push(Link {
+          source: Source::Note(path.clone(), i + 1),
+          name,
+        });
+      }
+    }
+  }
+
+  Ok((links, files, tasks))
+}
+
+/// Writes every task and note that links to what `name` refers to
+pub fn write_backlinks<W: Write>(
+  out: &mut W,
+  links: &[Link],
+  targets: &Targets,
+  name: &str,
+) -> Result<()> {
+  // links to a note that isn't written yet still count
+  let target = targets.resolve(name);
+  let found: Vec<&Link> = links
+    .iter()
+    .filter(|link| match &target {
+      Some(target) => targets.resolve(&link.name).as_ref() == Some(target),
+      None => link.name.to_lowercase() == name.trim().to_lowercase(),
+    })
+    .collect();
+
+  if found.is_empty() {
+    writeln!(out, "Nothing links to \"{}\"", name)?;
+    return Ok(());
+  }
+  for link in found {
+    writeln!(out, "{}", source_name(&link.source))?;
+  }
+
+  Ok(())
+}
+
+/// Writes the links that don't point to any note or task, failing when
+/// there are some so that it can be used in scripts
+pub fn check<W: Write>(out: &mut W, links: &[Link], targets: &Targets) -> Result<()> {
+  let broken: Vec<&Link> = links
+    .iter()
+    .filter(|link| targets.resolve(&link.name).is_none())
+    .collect();
+
+  if broken.is_empty() {
+    writeln!(out, "No broken links")?;
+    return Ok(());
+  }
+  for link in &broken {
+    writeln!(out, "{}: [[{}]]", source_name(&link.source), link.name)?;
+  }
+
+  Err(anyhow!("{} broken links", broken.len()))
+}
+
+fn source_name(source: &Source) -> String {
+  match source {
+    Source::Task(task) => format!("[{}] {}", task.status.marker(), task.description),
+    Source::Note(path, line) => format!("{}:{}", path.display(), line),
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use crate::tasks::TaskStatus;
+
+  fn task(description: &str) -> Task {
+    Task {
+      status: TaskStatus::Todo,
+      description: String::from(description),
+    }
+  }
+
+  #[test]
+  fn test_names() {
+    assert_eq!(
+      vec!["spec", "Meeting notes", "journal/2026-10-17"],
+      names("see [[spec]], [[ Meeting notes#Actions|the notes]] and [[journal/2026-10-17]] [[]]")
+    );
+    assert!(names("[not a link] [[unclosed").is_empty());
+  }
+
+  #[test]
+  fn test_resolve() {
+    let notes = [
+      PathBuf::from("notes/spec.md"),
+      PathBuf::from("notes/journal/2026-10-17.md"),
+    ];
+    let tasks = [task("call Bob #sales due:2026-10-20"), task("spec")];
+    let targets = Targets::new(&notes, &tasks);
+
+    assert_eq!(
+      Some(Target::Note(notes[0].clone())),
+      targets.resolve("Spec")
+    );
+    assert_eq!(
+      Some(Target::Note(notes[1].clone())),
+      targets.resolve("journal/2026-10-17")
+    );
+    assert_eq!(
+      Some(Target::Task(tasks[0].description.clone())),
+      targets.resolve("call bob")
+    );
+    assert_eq!(None, targets.resolve("call"));
+  }
+
+  #[test]
+  fn test_backlinks_and_check() {
+    let notes = [PathBuf::from("spec.md")];
+    let tasks = [task("write [[spec]]"), task("call Bob")];
+    let targets = Targets::new(&notes, &tasks);
+    let link = |source: Source, name: &str| Link {
+      source,
+      name: String::from(name),
+    };
+    let links = [
+      link(Source::Task(tasks[0].clone()), "spec"),
+      link(Source::Note(PathBuf::from("log.md"), 3), "SPEC"),
+      link(Source::Note(PathBuf::from("log.md"), 4), "call Bob"),
+      link(Source::Note(PathBuf::from("log.md"), 5), "draft"),
+    ];
+    let render = |result: &mut dyn FnMut(&mut Vec<u8>) -> Result<()>| {
+      let mut out = Vec::new();
+      let ok = result(&mut out).is_ok();
+      (String::from_utf8(out).unwrap(), ok)
+    };
+
+    assert_eq!(
+      ("[ ] write [[spec]]\nlog.md:3\n".to_string(), true),
+      render(&mut |out| write_backlinks(out, &links, &targets, "spec"))
+    );
+    assert_eq!(
+      ("log.md:5\n".to_string(), true),
+      render(&mut |out| write_backlinks(out, &links, &targets, "Draft"))
+    );
+    assert_eq!(
+      ("log.md:5: [[draft]]\n".to_string(), false),
+      render(&mut |out| check(out, &links, &targets))
+    );
+  }
+}
@@ -0,0 +1,87 @@
+use anyhow::Result;
+use std::path::{Path, PathBuf};
+
+use crate::config::Config;
+use crate::directory;
+use crate::markdown::block_end;
+use crate::storage::{StoreKind, StoreSpec};
+use crate::tasks::TaskList;
+
+pub mod links;
+
+/// Where the journal's daily notes go
+pub fn journal_dir(config: &Config, store: &StoreSpec) -> PathBuf {
+  match (&config.journal.dir, store.kind) {
+    (Some(dir), _) => PathBuf::from(dir),
+    (None, StoreKind::Directory) => Path::new(&store.path).join("journal"),
+    (None, _) => PathBuf::from("journal"),
+  }
+}
+
+/// The directories notes are kept in: the journal's and that of a `dir:` store
+pub fn dirs(config: &Config, store: &StoreSpec) -> Vec<PathBuf> {
+  let journal = journal_dir(config, store);
+  match store.kind {
+    StoreKind::Directory if journal.starts_with(&store.path) => vec![PathBuf::from(&store.path)],
+    StoreKind::Directory => vec![PathBuf::from(&store.path), journal],
+    _ => vec![journal],
+  }
+}
+
+/// Every markdown file under `dirs`
+pub fn files(dirs: &[PathBuf]) -> Result<Vec<PathBuf>> {
+  let mut paths = Vec::new();
+  for dir in dirs {
+    directory::scan(dir, &mut paths)?;
+  }
+  paths.sort();
+  paths.dedup();
+
+  Ok(paths)
+}
+
+/// The lines of a note with their index, leaving out tasks and what's
+/// indented under them as those are read from the store
+pub fn text_lines(lines: &[String]) -> Vec<(usize, &str)> {
+  let mut in_task = vec![false; lines.len()];
+  for (line, (start, ..)) in TaskList::md_tasks(lines) {
+    let end = block_end(lines, line, start);
+    in_task[line..end].fill(true);
+  }
+
+  lines
+    .iter()
+    .enumerate()
+    .filter(|(i, _)| !in_task[*i])
+    .map(|(i, line)| (i, line.as_str()))
+    .collect()
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn test_text_lines() {
+    let lines: Vec<String> = [
+      "# Monday",
+      "- [ ] call Bob",
+      "  about the spec",
+      "",
+      "Talked to Alice",
+      "- plain bullet",
+    ]
+    .map(String::from)
+    .to_vec();
+
+    assert_eq!(
+      vec![
+        (0, "# Monday"),
+        (3, ""),
+        (4, "Talked to Alice"),
+        (5, "- plain bullet")
+      ],
+      text_lines(&lines)
+    );
+  }
+}
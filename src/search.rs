@@ -0,0 +1,361 @@
+use anyhow::{Result, anyhow};
+use console::style;
+use regex::{Regex, RegexBuilder};
+use serde::Serialize;
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+
+use crate::notes;
+use crate::tasks::{GetTasksFilterOption, Task, TaskListPersist, TaskStatus};
+
+/// How many matching lines are shown for each result
+const SNIPPETS: usize = 3;
+/// How much of a long line is shown around the first match, in characters
+const SNIPPET_WIDTH: usize = 80;
+/// Matches in a task's description count for more than those in its notes
+const DESCRIPTION_WEIGHT: usize = 3;
+
+/// What to look for: every word of the query, or one regex
+pub struct Query {
+  patterns: Vec<Regex>,
+}
+
+impl Query {
+  pub fn new(input: &str, regex: bool, ignore_case: bool) -> Result<Query> {
+    let sources: Vec<String> = if regex {
+      vec![input.to_string()]
+    } else {
+      input.split_whitespace().map(regex::escape).collect()
+    };
+    if sources.iter().all(|source| source.is_empty()) {
+      return Err(anyhow!("Nothing to search for"));
+    }
+
+    let patterns = sources
+      .iter()
+      .map(|source| {
+        RegexBuilder::new(source)
+          .case_insensitive(ignore_case)
+          .build()
+      })
+      .collect::<Result<Vec<Regex>, regex::Error>>()?;
+    Ok(Query { patterns })
+  }
+
+  /// Where each pattern matches `text`, as byte ranges in order
+  fn matches(&self, text: &str) -> Vec<(usize, usize)> {
+    let mut ranges: Vec<(usize, usize)> = self
+      .patterns
+      .iter()
+      .flat_map(|pattern| pattern.find_iter(text))
+      .filter(|found| !found.is_empty())
+      .map(|found| (found.start(), found.end()))
+      .collect();
+    ranges.sort();
+    // words of the query can overlap, e.g. `spec` and `ec`
+    ranges.dedup_by(|next, last| {
+      if next.0 < last.1 {
+        last.1 = last.1.max(next.1);
+        true
+      } else {
+        false
+      }
+    });
+    ranges
+  }
+
+  /// Whether every pattern matches one of `texts`
+  fn matches_all<'t>(&self, texts: impl Iterator<Item = &'t str> + Clone) -> bool {
+    self
+      .patterns
+      .iter()
+      .all(|pattern| texts.clone().any(|text| pattern.is_match(text)))
+  }
+
+  pub fn search_task(&self, task: &Task, notes: &[String]) -> Option<SearchResult> {
+    let texts = [task.description.as_str()]
+      .into_iter()
+      .chain(notes.iter().map(String::as_str));
+    if !self.matches_all(texts) {
+      return None;
+    }
+
+    let matches = self.matches(&task.description);
+    let lines = notes.iter().map(String::as_str).enumerate();
+    let (snippets, count) = self.snippets(lines);
+    Some(SearchResult {
+      kind: Found::Task,
+      name: task.description.clone(),
+      status: Some(task.status),
+      score: matches.len() * DESCRIPTION_WEIGHT + count,
+      matches,
+      snippets,
+    })
+  }
+
+  /// Looks through the note at `path`, whose `lines` are numbered from 0
+  pub fn search_note<'l>(
+    &self,
+    path: &str,
+    lines: impl Iterator<Item = (usize, &'l str)> + Clone,
+  ) -> Option<SearchResult> {
+    if !self.matches_all(lines.clone().map(|(_, line)| line)) {
+      return None;
+    }
+
+    let (snippets, count) = self.snippets(lines);
+    Some(SearchResult {
+      kind: Found::Note,
+      name: path.to_string(),
+      status: None,
+      score: count,
+      matches: Vec::new(),
+      snippets,
+    })
+  }
+
+  /// The first few matching lines and how many matches there are in all
+  fn snippets<'l>(&self, lines: impl Iterator<Item = (usize, &'l str)>) -> (Vec<Snippet>, usize) {
+    let mut snippets = Vec::new();
+    let mut count = 0;
+    for (i, line) in lines {
+      let line = line.trim();
+      let matches = self.matches(line);
+      if matches.is_empty() {
+        continue;
+      }
+      count += matches.len();
+      if snippets.len() < SNIPPETS {
+        snippets.push(Snippet::new(i + 1, line, &matches));
+      }
+    }
+    (snippets, count)
+  }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Found {
+  Task,
+  Note,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct SearchResult {
+  pub kind: Found,
+  /// The task's description or the note's path
+  pub name: String,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub status: Option<TaskStatus>,
+  pub score: usize,
+  /// Where `name` matched, as byte ranges
+  pub matches: Vec<(usize, usize)>,
+  pub snippets: Vec<Snippet>,
+}
+
+/// A matching line, cut down around the first match when it's long
+#[derive(Debug, PartialEq, Serialize)]
+pub struct Snippet {
+  /// Counted from 1, in the task's notes or the note
+  pub line: usize,
+  pub text: String,
+  /// Byte ranges in `text`
+  pub matches: Vec<(usize, usize)>,
+}
+
+impl Snippet {
+  fn new(line: usize, text: &str, matches: &[(usize, usize)]) -> Snippet {
+    let chars: Vec<(usize, char)> = text.char_indices().collect();
+    if chars.len() <= SNIPPET_WIDTH {
+      return Snippet {
+        line,
+        text: text.to_string(),
+        matches: matches.to_vec(),
+      };
+    }
+
+    // start a little before the first match so it's read in context
+    let first = chars
+      .iter()
+      .position(|(at, _)| *at >= matches[0].0)
+      .unwrap_or_default();
+    let start_char = first
+      .saturating_sub(SNIPPET_WIDTH / 4)
+      .min(chars.len() - SNIPPET_WIDTH);
+    let start = chars[start_char].0;
+    let end = chars
+      .get(start_char + SNIPPET_WIDTH)
+      .map_or(text.len(), |(at, _)| *at);
+
+    let prefix = if start > 0 { "…" } else { "" };
+    let suffix = if end < text.len() { "…" } else { "" };
+    let shift = |at: usize| at - start + prefix.len();
+    Snippet {
+      line,
+      text: format!("{}{}{}", prefix, &text[start..end], suffix),
+      matches: matches
+        .iter()
+        .filter(|(from, to)| *from >= start && *to <= end)
+        .map(|(from, to)| (shift(*from), shift(*to)))
+        .collect(),
+    }
+  }
+}
+
+/// Searches the tasks `filter` picks, their notes and, unless only open or
+/// only completed tasks are wanted, the notes under `note_dirs`. Best first.
+pub fn search<S: TaskListPersist>(
+  storage: &mut S,
+  note_dirs: &[PathBuf],
+  query: &Query,
+  filter: &GetTasksFilterOption,
+) -> Result<Vec<SearchResult>> {
+  let mut results = Vec::new();
+  let tasks = storage.load_tasklist()?.get_tasks(filter);
+  // not every store keeps notes
+  let notes = storage.load_all_notes().unwrap_or_default();
+  for task in tasks {
+    let notes = notes.get(&task.description).map(Vec::as_slice);
+    results.extend(query.search_task(&task, notes.unwrap_or_default()));
+  }
+
+  if let GetTasksFilterOption::All = filter {
+    for path in notes::files(note_dirs)? {
+      let lines: Vec<String> = fs::read_to_string(&path)?
+        .lines()
+        .map(String::from)
+        .collect();
+      let text = notes::text_lines(&lines);
+      results.extend(query.search_note(&path.to_string_lossy(), text.into_iter()));
+    }
+  }
+
+  results.sort_by(|a, b| {
+    let closed = |result: &SearchResult| result.status.is_some_and(|status| status.is_closed());
+    b.score
+      .cmp(&a.score)
+      .then(closed(a).cmp(&closed(b)))
+      .then(a.name.cmp(&b.name))
+  });
+  Ok(results)
+}
+
+/// Writes the results with their matches highlighted, or as JSON
+pub fn write<W: Write>(out: &mut W, results: &[SearchResult], json: bool) -> Result<()> {
+  if json {
+    serde_json::to_writer_pretty(&mut *out, results)?;
+    writeln!(out)?;
+    return Ok(());
+  }
+
+  if results.is_empty() {
+    writeln!(out, "No matches")?;
+    return Ok(());
+  }
+
+  for result in results {
+    let name = highlight(&result.name, &result.matches);
+    match result.status {
+      Some(status) => writeln!(out, "[{}] {}", status.marker(), name)?,
+      None => writeln!(out, "{}", style(name).underlined())?,
+    }
+    for snippet in &result.snippets {
+      writeln!(
+        out,
+        "  {:>4}: {}",
+        snippet.line,
+        highlight(&snippet.text, &snippet.matches)
+      )?;
+    }
+  }
+
+  Ok(())
+}
+
+fn highlight(text: &str, matches: &[(usize, usize)]) -> String {
+  let mut highlighted = String::new();
+  let mut last = 0;
+  for (start, end) in matches {
+    highlighted.push_str(&text[last..*start]);
+    highlighted.push_str(&style(&text[*start..*end]).bold().yellow().to_string());
+    last = *end;
+  }
+  highlighted.push_str(&text[last..]);
+  highlighted
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  fn task(status: TaskStatus, description: &str) -> Task {
+    Task {
+      status,
+      description: String::from(description),
+    }
+  }
+
+  fn notes(lines: &[&str]) -> Vec<String> {
+    lines.iter().map(|line| line.to_string()).collect()
+  }
+
+  #[test]
+  fn test_every_word_has_to_match() {
+    let query = Query::new("spec bob", false, true).unwrap();
+    let call = task(TaskStatus::Todo, "call Bob");
+
+    assert!(query.search_task(&call, &[]).is_none());
+
+    let result = query
+      .search_task(&call, &notes(&["about the spec", "", "Bob's spec too"]))
+      .unwrap();
+    assert_eq!(vec![(5, 8)], result.matches);
+    assert_eq!(DESCRIPTION_WEIGHT + 3, result.score);
+    assert_eq!(
+      vec![(1, vec![(10, 14)]), (3, vec![(0, 3), (6, 10)])],
+      result
+        .snippets
+        .iter()
+        .map(|snippet| (snippet.line, snippet.matches.clone()))
+        .collect::<Vec<_>>()
+    );
+
+    let case_sensitive = Query::new("spec bob", false, false).unwrap();
+    assert!(
+      case_sensitive
+        .search_task(&call, &notes(&["about the spec"]))
+        .is_none()
+    );
+  }
+
+  #[test]
+  fn test_regex() {
+    let query = Query::new(r"v\d+\.\d+", true, false).unwrap();
+    let lines = ["# Release", "", "Shipping v1.2 and v1.3 today"];
+    let result = query
+      .search_note("journal/2026-10-17.md", lines.into_iter().enumerate())
+      .unwrap();
+
+    assert_eq!(2, result.score);
+    assert_eq!(3, result.snippets[0].line);
+    assert!(Query::new("(", true, false).is_err());
+    assert!(Query::new("(", false, false).is_ok());
+    assert!(Query::new(" ", false, false).is_err());
+  }
+
+  #[test]
+  fn test_long_lines_are_cut_around_the_match() {
+    let line = format!("{} needle {}", "é".repeat(100), "x".repeat(100));
+    let query = Query::new("needle", false, false).unwrap();
+    let result = query
+      .search_note("note.md", [(0, line.as_str())].into_iter())
+      .unwrap();
+    let snippet = &result.snippets[0];
+
+    assert_eq!(SNIPPET_WIDTH + 2, snippet.text.chars().count());
+    assert!(snippet.text.starts_with('…') && snippet.text.ends_with('…'));
+    let (start, end) = snippet.matches[0];
+    assert_eq!("needle", &snippet.text[start..end]);
+  }
+}
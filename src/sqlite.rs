@@ -0,0 +1,547 @@
+use anyhow::{Result, anyhow};
+use chrono::{DateTime, Local, NaiveDate};
+use log::{debug, info};
+use rusqlite::{Connection, Transaction, params, params_from_iter, types::Value};
+use std::collections::HashMap;
+use std::fs;
+use uuid::Uuid;
+
+use crate::storage;
+use crate::tasks::filter::{Comparison, Condition};
+use crate::tasks::{
+  Filter, GetTasksFilterOption, Task, TaskChange, TaskList, TaskListPersist, TaskMeta, TaskStatus,
+};
+use crate::timer::{TaskTime, TimeEntry};
+
+/// Each entry upgrades the schema by one version, tracked in `user_version`
+const MIGRATIONS: &[&str] = &[
+  "CREATE TABLE tasks (
+    id INTEGER PRIMARY KEY,
+    uuid TEXT NOT NULL UNIQUE,
+    position INTEGER NOT NULL,
+    description TEXT NOT NULL,
+    is_completed INTEGER NOT NULL DEFAULT 0,
+    created TEXT NOT NULL,
+    completed TEXT
+  );
+  CREATE INDEX tasks_is_completed ON tasks (is_completed);
+  CREATE INDEX tasks_description ON tasks (description);",
+  "ALTER TABLE tasks ADD COLUMN due TEXT;
+  CREATE INDEX tasks_due ON tasks (due);
+  CREATE TABLE task_tags (
+    task_id INTEGER NOT NULL REFERENCES tasks (id) ON DELETE CASCADE,
+    tag TEXT NOT NULL COLLATE NOCASE,
+    PRIMARY KEY (task_id, tag)
+  );
+  CREATE INDEX task_tags_tag ON task_tags (tag);",
+  "ALTER TABLE tasks ADD COLUMN status TEXT NOT NULL DEFAULT ' ';
+  UPDATE tasks SET status = 'x' WHERE is_completed;
+  CREATE INDEX tasks_status ON tasks (status);",
+  "CREATE TABLE time_entries (
+    id INTEGER PRIMARY KEY,
+    task_id INTEGER NOT NULL REFERENCES tasks (id) ON DELETE CASCADE,
+    start TEXT NOT NULL,
+    end TEXT
+  );
+  CREATE INDEX time_entries_task_id ON time_entries (task_id);",
+  "ALTER TABLE time_entries ADD COLUMN pomodoro INTEGER NOT NULL DEFAULT 0;",
+  "ALTER TABLE tasks ADD COLUMN notes TEXT NOT NULL DEFAULT '';",
+];
+
+/// Matches the rows whose status marker means done or cancelled
+const CLOSED_SQL: &str = "status IN ('x', '-')";
+
+pub struct Database<'a> {
+  pub path: &'a str,
+  conn: Option<Connection>,
+  lock: Option<fs::File>,
+}
+
+impl<'a> Database<'a> {
+  pub fn from(path: &'a str) -> Database<'a> {
+    Database {
+      path,
+      conn: None,
+      lock: None,
+    }
+  }
+
+  fn connection(&mut self) -> Result<&mut Connection> {
+    if self.conn.is_none() {
+      let mut conn = Connection::open(self.path)?;
+      conn.pragma_update(None, "foreign_keys", true)?;
+      migrate(&mut conn)?;
+      self.conn = Some(conn);
+    }
+
+    Ok(self.conn.as_mut().unwrap())
+  }
+
+  fn query(&mut self, condition: &str, params: Vec<Value>) -> Result<TaskList> {
+    let sql = format!(
+      "SELECT description, status FROM tasks WHERE {} ORDER BY position",
+      condition
+    );
+    debug!("{} {:?}", sql, params);
+
+    let conn = self.connection()?;
+    let mut stmt = conn.prepare(&sql)?;
+    let tasks = stmt
+      .query_map(params_from_iter(params), |row| {
+        let marker: String = row.get(1)?;
+        Ok(Task {
+          description: row.get(0)?,
+          status: TaskStatus::from_marker(marker.chars().next().unwrap_or(' ')),
+        })
+      })?
+      .collect::<rusqlite::Result<Vec<Task>>>()?;
+
+    Ok(TaskList::from(tasks))
+  }
+}
+
+fn migrate(conn: &mut Connection) -> Result<()> {
+  let version: i64 = conn.pragma_query_value(None, "user_version", |row| row.get(0))?;
+  let version = version as usize;
+  if version > MIGRATIONS.len() {
+    return Err(anyhow!(
+      "Database was written by a newer version of taskmaster"
+    ));
+  }
+
+  for (i, migration) in MIGRATIONS.iter().enumerate().skip(version) {
+    info!("migrating database to version {}", i + 1);
+    let tx = conn.transaction()?;
+    tx.execute_batch(migration)?;
+    tx.pragma_update(None, "user_version", (i + 1) as i64)?;
+    tx.commit()?;
+  }
+
+  Ok(())
+}
+
+/// Turns a filter into a `WHERE` clause, or `None` when it can't be written
+/// in SQL. It's only used to narrow down what gets loaded; the filter is
+/// still applied to what comes back. Nothing is added to `params` for `None`.
+fn filter_sql(filter: &Filter, today: NaiveDate, params: &mut Vec<Value>) -> Option<String> {
+  match filter {
+    Filter::And(a, b) => match (filter_sql(a, today, params), filter_sql(b, today, params)) {
+      (Some(a), Some(b)) => Some(format!("({} AND {})", a, b)),
+      // either side still narrows it down on its own
+      (sql, None) | (None, sql) => sql,
+    },
+    Filter::Or(a, b) => {
+      let start = params.len();
+      match (filter_sql(a, today, params), filter_sql(b, today, params)) {
+        (Some(a), Some(b)) => Some(format!("({} OR {})", a, b)),
+        _ => {
+          params.truncate(start);
+          None
+        }
+      }
+    }
+    // narrowing down only works one way, so what's negated has to be
+    // written in full
+    Filter::Not(f) if !is_sql(f) => None,
+    Filter::Not(f) => Some(format!("NOT {}", filter_sql(f, today, params)?)),
+    Filter::Cond(Condition::Open(open)) => match open {
+      true => Some(format!("NOT {}", CLOSED_SQL)),
+      false => Some(String::from(CLOSED_SQL)),
+    },
+    Filter::Cond(Condition::Status(status)) => {
+      params.push(Value::from(status.marker().to_string()));
+      Some(String::from("status = ?"))
+    }
+    Filter::Cond(Condition::Tag(tag)) => {
+      params.push(Value::from(tag.clone()));
+      Some(String::from(
+        "EXISTS (SELECT 1 FROM task_tags WHERE task_id = tasks.id AND tag = ?)",
+      ))
+    }
+    Filter::Cond(Condition::Due(cmp, expr)) => {
+      let Some(date) = expr.resolve(today) else {
+        return Some(String::from("0"));
+      };
+      let op = match cmp {
+        Comparison::Eq => "=",
+        Comparison::Lt => "<",
+        Comparison::Le => "<=",
+        Comparison::Gt => ">",
+        Comparison::Ge => ">=",
+      };
+      params.push(Value::from(date.format("%Y-%m-%d").to_string()));
+      // never NULL so that NOT works the same as it does in memory
+      Some(format!("(due IS NOT NULL AND due {} ?)", op))
+    }
+    Filter::Cond(Condition::Text(_)) if !is_sql(filter) => None,
+    Filter::Cond(Condition::Text(text)) => {
+      params.push(Value::from(text.clone()));
+      Some(String::from("instr(lower(description), lower(?)) > 0"))
+    }
+  }
+}
+
+/// Whether all of the filter can be written in SQL
+fn is_sql(filter: &Filter) -> bool {
+  match filter {
+    Filter::And(a, b) | Filter::Or(a, b) => is_sql(a) && is_sql(b),
+    Filter::Not(f) => is_sql(f),
+    // lower() only folds ASCII, where the filter folds every letter
+    Filter::Cond(Condition::Text(text)) => text.is_ascii(),
+    Filter::Cond(_) => true,
+  }
+}
+
+fn parse_time(value: &str) -> Result<DateTime<Local>> {
+  Ok(DateTime::parse_from_rfc3339(value)?.with_timezone(&Local))
+}
+
+fn insert_task(tx: &Transaction, task: &Task) -> Result<()> {
+  let now = Local::now().to_rfc3339();
+  let due = TaskMeta::parse(&task.description)
+    .due
+    .map(|due| due.format("%Y-%m-%d").to_string());
+
+  tx.execute(
+    "INSERT INTO tasks (uuid, position, description, is_completed, status, created, completed, due)
+     VALUES (?1, (SELECT COALESCE(MAX(position), 0) + 1 FROM tasks), ?2, ?3, ?4, ?5, ?6, ?7)",
+    params![
+      Uuid::new_v4().to_string(),
+      task.description,
+      task.status.is_completed(),
+      task.status.marker().to_string(),
+      now,
+      task.status.is_completed().then_some(&now),
+      due,
+    ],
+  )?;
+  set_tags(tx, tx.last_insert_rowid(), &task.description)
+}
+
+fn update_task(tx: &Transaction, original: &str, task: &Task) -> Result<()> {
+  let id: Option<i64> = tx
+    .query_row(
+      "SELECT id FROM tasks WHERE description = ?1",
+      [original],
+      |row| row.get(0),
+    )
+    .ok();
+  let Some(id) = id else {
+    return Ok(());
+  };
+
+  let due = TaskMeta::parse(&task.description)
+    .due
+    .map(|due| due.format("%Y-%m-%d").to_string());
+  tx.execute(
+    "UPDATE tasks SET
+       description = ?2,
+       completed = CASE
+         WHEN ?3 = is_completed THEN completed
+         WHEN ?3 THEN ?4
+         ELSE NULL
+       END,
+       is_completed = ?3,
+       due = ?5,
+       status = ?6
+     WHERE id = ?1",
+    params![
+      id,
+      task.description,
+      task.status.is_completed(),
+      Local::now().to_rfc3339(),
+      due,
+      task.status.marker().to_string()
+    ],
+  )?;
+  set_tags(tx, id, &task.description)
+}
+
+fn set_tags(tx: &Transaction, id: i64, description: &str) -> Result<()> {
+  tx.execute("DELETE FROM task_tags WHERE task_id = ?1", [id])?;
+  for tag in TaskMeta::parse(description).tags {
+    tx.execute(
+      "INSERT OR IGNORE INTO task_tags (task_id, tag) VALUES (?1, ?2)",
+      params![id, tag],
+    )?;
+  }
+
+  Ok(())
+}
+
+impl<'a> TaskListPersist for Database<'a> {
+  fn lock(&mut self) -> Result<()> {
+    self.lock = Some(storage::lock(self.path)?);
+    Ok(())
+  }
+
+  fn unlock(&mut self) {
+    self.lock = None;
+  }
+
+  fn load_tasklist(&mut self) -> Result<TaskList> {
+    info!("loading tasks from database");
+    self.query("1", Vec::new())
+  }
+
+  fn load_tasklist_filtered(&mut self, list_option: &GetTasksFilterOption) -> Result<TaskList> {
+    let mut params = Vec::new();
+    let condition = match list_option {
+      GetTasksFilterOption::All | GetTasksFilterOption::AllWithDeleted => String::from("1"),
+      GetTasksFilterOption::Completed => String::from(CLOSED_SQL),
+      GetTasksFilterOption::Incomplete => format!("NOT {}", CLOSED_SQL),
+      GetTasksFilterOption::Query(filter) => {
+        filter_sql(filter, Local::now().date_naive(), &mut params)
+          .unwrap_or_else(|| String::from("1"))
+      }
+    };
+
+    self.query(&condition, params)
+  }
+
+  fn save_tasklist(&mut self, tasklist: &mut TaskList) -> Result<()> {
+    let conn = self.connection()?;
+    let tx = conn.transaction()?;
+
+    for change in tasklist.changes() {
+      debug!("applying {:?}", change);
+      match change {
+        TaskChange::Added(task) => insert_task(&tx, &task)?,
+        TaskChange::Updated { original, task } => update_task(&tx, &original, &task)?,
+        TaskChange::Deleted(original) => {
+          tx.execute("DELETE FROM tasks WHERE description = ?1", [original])?;
+        }
+      }
+    }
+
+    tx.commit()?;
+
+    Ok(())
+  }
+
+  fn load_time_entries(&mut self) -> Result<Vec<TaskTime>> {
+    let conn = self.connection()?;
+    let mut stmt = conn.prepare(
+      "SELECT tasks.description, time_entries.start, time_entries.end, time_entries.pomodoro
+       FROM time_entries JOIN tasks ON tasks.id = time_entries.task_id
+       ORDER BY time_entries.start",
+    )?;
+    let entries = stmt
+      .query_map([], |row| {
+        Ok((
+          row.get::<_, String>(0)?,
+          row.get::<_, String>(1)?,
+          row.get::<_, Option<String>>(2)?,
+          row.get::<_, bool>(3)?,
+        ))
+      })?
+      .collect::<rusqlite::Result<Vec<(String, String, Option<String>, bool)>>>()?;
+
+    entries
+      .into_iter()
+      .map(|(task, start, end, pomodoro)| {
+        Ok(TaskTime {
+          task,
+          section: None,
+          entry: TimeEntry {
+            start: parse_time(&start)?,
+            end: end.as_deref().map(parse_time).transpose()?,
+            pomodoro,
+          },
+        })
+      })
+      .collect()
+  }
+
+  fn save_time_entries(&mut self, task: &str, entries: &[TimeEntry]) -> Result<()> {
+    let conn = self.connection()?;
+    let tx = conn.transaction()?;
+
+    let id: i64 = tx
+      .query_row(
+        "SELECT id FROM tasks WHERE description = ?1",
+        [task],
+        |row| row.get(0),
+      )
+      .map_err(|_| anyhow!("No task \"{}\"", task))?;
+    tx.execute("DELETE FROM time_entries WHERE task_id = ?1", [id])?;
+    for entry in entries {
+      tx.execute(
+        "INSERT INTO time_entries (task_id, start, end, pomodoro) VALUES (?1, ?2, ?3, ?4)",
+        params![
+          id,
+          entry.start.to_rfc3339(),
+          entry.end.map(|end| end.to_rfc3339()),
+          entry.pomodoro
+        ],
+      )?;
+    }
+
+    tx.commit()?;
+
+    Ok(())
+  }
+
+  fn load_notes(&mut self, task: &str) -> Result<Vec<String>> {
+    let notes: String = self
+      .connection()?
+      .query_row(
+        "SELECT notes FROM tasks WHERE description = ?1",
+        [task],
+        |row| row.get(0),
+      )
+      .map_err(|_| anyhow!("No task \"{}\"", task))?;
+
+    Ok(notes.lines().map(String::from).collect())
+  }
+
+  fn save_notes(&mut self, task: &str, notes: &[String]) -> Result<()> {
+    let changed = self.connection()?.execute(
+      "UPDATE tasks SET notes = ?1 WHERE description = ?2",
+      params![notes.join("\n"), task],
+    )?;
+    if changed == 0 {
+      return Err(anyhow!("No task \"{}\"", task));
+    }
+
+    Ok(())
+  }
+
+  fn load_all_notes(&mut self) -> Result<HashMap<String, Vec<String>>> {
+    let conn = self.connection()?;
+    let mut stmt = conn.prepare("SELECT description, notes FROM tasks WHERE notes != ''")?;
+    let rows = stmt.query_map([], |row| {
+      let notes: String = row.get(1)?;
+      Ok((row.get(0)?, notes.lines().map(String::from).collect()))
+    })?;
+
+    Ok(rows.collect::<rusqlite::Result<_>>()?)
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use crate::tasks::TaskUpdateAction;
+
+  fn database(name: &str) -> (std::path::PathBuf, String) {
+    let dir = std::env::temp_dir().join(format!("taskmaster-{}-{}", name, std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("tasks.db").to_string_lossy().to_string();
+    (dir, path)
+  }
+
+  #[test]
+  fn test_save_and_load_filtered() {
+    let (dir, path) = database("sqlite");
+
+    let mut db = Database::from(&path);
+    let mut tasklist = db.load_tasklist().unwrap();
+    tasklist
+      .add_task(String::from("deploy api #backend due:2026-10-19"))
+      .unwrap();
+    tasklist
+      .add_task(String::from("deploy ui #frontend"))
+      .unwrap();
+    tasklist.add_task(String::from("write docs")).unwrap();
+    db.save_tasklist(&mut tasklist).unwrap();
+
+    let mut db = Database::from(&path);
+    let mut tasklist = db.load_tasklist().unwrap();
+    tasklist.update_task(TaskUpdateAction::Toggle, "deploy ui #frontend");
+    tasklist.update_task(
+      TaskUpdateAction::Edit("write more docs #backend"),
+      "write docs",
+    );
+    db.save_tasklist(&mut tasklist).unwrap();
+
+    let load = |db: &mut Database, query: &str| -> Vec<String> {
+      let option = GetTasksFilterOption::Query(Filter::parse(query).unwrap());
+      let tasklist = db.load_tasklist_filtered(&option).unwrap();
+      tasklist
+        .get_tasks(&option)
+        .into_iter()
+        .map(|task| task.description)
+        .collect()
+    };
+
+    assert_eq!(
+      vec![
+        "deploy api #backend due:2026-10-19",
+        "write more docs #backend"
+      ],
+      load(&mut db, "tag:backend")
+    );
+    assert_eq!(vec!["deploy ui #frontend"], load(&mut db, "status:done"));
+    assert_eq!(
+      vec![
+        "deploy api #backend due:2026-10-19",
+        "write more docs #backend"
+      ],
+      load(&mut db, "status:open")
+    );
+    assert_eq!(
+      vec!["deploy ui #frontend", "write more docs #backend"],
+      load(&mut db, "not due<2026-10-20 or text~UI")
+    );
+
+    let completed = db
+      .load_tasklist_filtered(&GetTasksFilterOption::Completed)
+      .unwrap();
+    assert_eq!(1, completed.get_tasks(&GetTasksFilterOption::All).len());
+
+    let mut tasklist = db.load_tasklist().unwrap();
+    tasklist.add_task(String::from("order Café beans")).unwrap();
+    db.save_tasklist(&mut tasklist).unwrap();
+    assert_eq!(vec!["order Café beans"], load(&mut db, "text:CAFÉ"));
+    assert_eq!(
+      vec!["order Café beans"],
+      load(&mut db, "(text:é or tag:nothing) and status:open")
+    );
+    // none of the backend tasks mention é, so none are left out
+    assert_eq!(4, load(&mut db, "not (tag:backend and text:é)").len());
+
+    fs::remove_dir_all(&dir).unwrap();
+  }
+
+  #[test]
+  fn test_load_time_entries() {
+    let (dir, path) = database("sqlite-time");
+
+    let mut db = Database::from(&path);
+    let mut tasklist = db.load_tasklist().unwrap();
+    tasklist.add_task(String::from("write docs")).unwrap();
+    db.save_tasklist(&mut tasklist).unwrap();
+    db.connection()
+      .unwrap()
+      .execute(
+        "INSERT INTO time_entries (task_id, start, end)
+         VALUES (1, '2026-10-17T09:00:00+00:00', '2026-10-17T10:30:00+00:00')",
+        [],
+      )
+      .unwrap();
+
+    let entries = db.load_time_entries().unwrap();
+    assert_eq!(1, entries.len());
+    assert_eq!("write docs", entries[0].task);
+    let entry = &entries[0].entry;
+    assert_eq!(90, (entry.end.unwrap() - entry.start).num_minutes());
+
+    fs::remove_dir_all(&dir).unwrap();
+  }
+
+  #[test]
+  fn test_migrations_are_applied_once() {
+    let (dir, path) = database("sqlite-migrate");
+
+    Database::from(&path).connection().unwrap();
+    let mut db = Database::from(&path);
+    let conn = db.connection().unwrap();
+    let version: i64 = conn
+      .pragma_query_value(None, "user_version", |row| row.get(0))
+      .unwrap();
+    assert_eq!(MIGRATIONS.len() as i64, version);
+
+    fs::remove_dir_all(&dir).unwrap();
+  }
+}
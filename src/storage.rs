@@ -0,0 +1,309 @@
+use anyhow::{Result, anyhow};
+use log::{debug, info};
+use std::collections::HashMap;
+use std::fs::{self, OpenOptions, TryLockError};
+use std::io::Write;
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::config::Config;
+use crate::tasks::{GetTasksFilterOption, TaskListPersist, TaskStatus, TaskUpdateAction, io};
+use crate::timer::TimeEntry;
+use crate::{directory, json, markdown, sqlite, todotxt};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum StoreKind {
+  Markdown,
+  Json,
+  Sqlite,
+  TodoTxt,
+  Directory,
+}
+
+/// Where tasks are kept, written as `<kind>:<path>`, e.g. `sqlite:tasks.db`.
+/// The path can be left out to use the default file for that kind.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StoreSpec {
+  pub kind: StoreKind,
+  pub path: String,
+}
+
+impl StoreKind {
+  fn default_path(&self) -> &'static str {
+    match self {
+      StoreKind::Markdown => "tasks.md",
+      StoreKind::Json => "tasks.json",
+      StoreKind::Sqlite => "tasks.db",
+      StoreKind::TodoTxt => "todo.txt",
+      StoreKind::Directory => "notes",
+    }
+  }
+}
+
+impl Default for StoreSpec {
+  fn default() -> StoreSpec {
+    StoreSpec {
+      kind: StoreKind::Markdown,
+      path: StoreKind::Markdown.default_path().to_string(),
+    }
+  }
+}
+
+impl FromStr for StoreKind {
+  type Err = anyhow::Error;
+
+  fn from_str(kind: &str) -> Result<StoreKind> {
+    match kind {
+      "md" | "markdown" => Ok(StoreKind::Markdown),
+      "json" => Ok(StoreKind::Json),
+      "sqlite" | "db" => Ok(StoreKind::Sqlite),
+      "todo" | "todotxt" => Ok(StoreKind::TodoTxt),
+      "dir" | "directory" => Ok(StoreKind::Directory),
+      _ => Err(anyhow!("Unknown store \"{}\"", kind)),
+    }
+  }
+}
+
+impl FromStr for StoreSpec {
+  type Err = anyhow::Error;
+
+  fn from_str(spec: &str) -> Result<StoreSpec> {
+    let (kind, path) = match spec.split_once(':') {
+      Some((kind, path)) => (kind.parse::<StoreKind>()?, path.to_string()),
+      None => match spec.parse::<StoreKind>() {
+        Ok(kind) => (kind, kind.default_path().to_string()),
+        // a bare path, going by its extension
+        Err(_) if spec.ends_with(".json") => (StoreKind::Json, spec.to_string()),
+        Err(_) if spec.ends_with(".txt") => (StoreKind::TodoTxt, spec.to_string()),
+        Err(_) if spec.ends_with('/') => (StoreKind::Directory, spec.to_string()),
+        Err(_) => (StoreKind::Markdown, spec.to_string()),
+      },
+    };
+
+    if path.is_empty() {
+      return Ok(StoreSpec {
+        kind,
+        path: kind.default_path().to_string(),
+      });
+    }
+
+    Ok(StoreSpec { kind, path })
+  }
+}
+
+pub fn open<'a>(spec: &'a StoreSpec, config: &Config) -> Box<dyn TaskListPersist + 'a> {
+  match spec.kind {
+    StoreKind::Markdown => Box::new(
+      markdown::File::from(&spec.path)
+        .with_backup(config.backup)
+        .with_conflict_resolver(io::resolve_conflict),
+    ),
+    StoreKind::Json => Box::new(json::File::from(&spec.path).with_backup(config.backup)),
+    StoreKind::Sqlite => Box::new(sqlite::Database::from(&spec.path)),
+    StoreKind::TodoTxt => Box::new(todotxt::File::from(&spec.path).with_backup(config.backup)),
+    StoreKind::Directory => Box::new(
+      directory::Directory::from(&spec.path)
+        .with_backup(config.backup)
+        .with_conflict_resolver(io::resolve_conflict),
+    ),
+  }
+}
+
+impl StoreSpec {
+  /// Whether both specs point at the same file or directory, which would
+  /// share a lock
+  pub fn same_path(&self, other: &StoreSpec) -> bool {
+    let resolve = |path: &str| fs::canonicalize(path).or_else(|_| std::path::absolute(path));
+    match (resolve(&self.path), resolve(&other.path)) {
+      (Ok(path), Ok(other)) => path == other,
+      _ => Path::new(&self.path) == Path::new(&other.path),
+    }
+  }
+}
+
+/// Copies every task from one store into another, which must not have any
+/// tasks yet. Returns the number of tasks copied.
+pub fn migrate(from: &StoreSpec, to: &StoreSpec, config: &Config) -> Result<usize> {
+  // locking the same file twice would wait forever
+  if from.same_path(to) {
+    return Err(anyhow!("Can't migrate {} to itself", from.path));
+  }
+
+  let mut from = open(from, config);
+  let mut to = open(to, config);
+  from.lock()?;
+  to.lock()?;
+
+  copy(&mut from, &mut to)
+}
+
+/// Copies the tasks, with their notes and time, into a store without tasks
+fn copy(from: &mut dyn TaskListPersist, to: &mut dyn TaskListPersist) -> Result<usize> {
+  let source = from.load_tasklist()?;
+  let mut target = to.load_tasklist()?;
+  if !target.get_tasks(&GetTasksFilterOption::All).is_empty() {
+    return Err(anyhow!("The store to migrate to already has tasks"));
+  }
+
+  // not every store keeps notes or time, which is only a problem when
+  // there's some to copy
+  let notes = from.load_all_notes().unwrap_or_default();
+  if !notes.is_empty() && to.load_all_notes().is_err() {
+    return Err(anyhow!("The store to migrate to can't keep notes"));
+  }
+  let mut entries: HashMap<String, Vec<TimeEntry>> = HashMap::new();
+  for time in from.load_time_entries().unwrap_or_default() {
+    entries.entry(time.task).or_default().push(time.entry);
+  }
+  if !entries.is_empty() && to.load_time_entries().is_err() {
+    return Err(anyhow!("The store to migrate to can't keep track of time"));
+  }
+
+  let tasks = source.get_tasks(&GetTasksFilterOption::All);
+  for task in tasks.iter() {
+    target.add_task(task.description.clone())?;
+    if task.status != TaskStatus::Todo {
+      target.update_task(TaskUpdateAction::SetStatus(task.status), &task.description);
+    }
+  }
+
+  to.save_tasklist(&mut target)?;
+  // both go under tasks the store has, so only once they're saved
+  for (description, notes) in notes {
+    to.save_notes(&description, &notes)?;
+  }
+  for (description, entries) in entries {
+    to.save_time_entries(&description, &entries)?;
+  }
+  info!("migrated {} tasks", tasks.len());
+
+  Ok(tasks.len())
+}
+
+/// Takes an advisory lock on `<path>.lock`, held until the returned file is
+/// dropped. A separate file is locked as stores replace their file on save.
+pub fn lock(path: &str) -> Result<fs::File> {
+  let lock_file = OpenOptions::new()
+    .write(true)
+    .create(true)
+    .truncate(false)
+    .open(format!("{}.lock", path))?;
+
+  match lock_file.try_lock() {
+    Ok(()) => {}
+    Err(TryLockError::WouldBlock) => {
+      info!("waiting for lock on {}", path);
+      eprintln!("Waiting for another taskmaster to finish with {}...", path);
+      lock_file.lock()?;
+    }
+    Err(TryLockError::Error(err)) => return Err(err.into()),
+  }
+
+  Ok(lock_file)
+}
+
+/// Writes to a temporary file first and renames it over the original so
+/// a failed write never leaves a truncated file behind
+pub fn write_atomic(path: &str, contents: &str, backup: bool) -> Result<()> {
+  let tmp_path = format!("{}.tmp", path);
+
+  let mut file = OpenOptions::new()
+    .write(true)
+    .truncate(true)
+    .create(true)
+    .open(&tmp_path)?;
+
+  let written = file
+    .write_all(contents.as_bytes())
+    .and_then(|_| file.sync_all());
+  if let Err(err) = written {
+    let _ = fs::remove_file(&tmp_path);
+    return Err(err.into());
+  }
+
+  if let Ok(metadata) = fs::metadata(path) {
+    fs::set_permissions(&tmp_path, metadata.permissions())?;
+
+    if backup {
+      debug!("backing up {}", path);
+      fs::copy(path, format!("{}.bak", path))?;
+    }
+  }
+
+  fs::rename(&tmp_path, path)?;
+
+  Ok(())
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use chrono::Local;
+
+  #[test]
+  fn test_migrate_keeps_notes_and_time() {
+    let dir = std::env::temp_dir().join(format!("taskmaster-migrate-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let md_path = dir.join("tasks.md").to_string_lossy().to_string();
+    let json_path = dir.join("tasks.json").to_string_lossy().to_string();
+    fs::write(
+      &md_path,
+      "- [ ] write spec\n  see the draft\n  - ⏱ 2026-10-17 09:00-10:30\n- [x] call Bob\n",
+    )
+    .unwrap();
+
+    let mut from = markdown::File::from(md_path.as_str());
+    let mut to = json::File::from(json_path.as_str());
+    assert_eq!(2, copy(&mut from, &mut to).unwrap());
+
+    let mut to = json::File::from(json_path.as_str());
+    assert_eq!(vec!["see the draft"], to.load_notes("write spec").unwrap());
+    let entries = to.load_time_entries().unwrap();
+    assert_eq!(1, entries.len());
+    assert_eq!("write spec", entries[0].task);
+    assert_eq!(90, entries[0].entry.minutes(Local::now()));
+
+    fs::remove_dir_all(&dir).unwrap();
+  }
+
+  #[test]
+  fn test_same_path() {
+    let spec = |spec: &str| spec.parse::<StoreSpec>().unwrap();
+
+    assert!(spec("md:tasks.md").same_path(&spec("json:./tasks.md")));
+    assert!(spec("dir:notes/").same_path(&spec("dir:notes")));
+    assert!(!spec("tasks.md").same_path(&spec("tasks.json")));
+  }
+
+  #[test]
+  fn test_parse_store_spec() {
+    let spec = |kind, path: &str| StoreSpec {
+      kind,
+      path: path.to_string(),
+    };
+
+    assert_eq!(
+      spec(StoreKind::Json, "data/tasks.json"),
+      "json:data/tasks.json".parse().unwrap()
+    );
+    assert_eq!(spec(StoreKind::Json, "tasks.json"), "json".parse().unwrap());
+    assert_eq!(
+      spec(StoreKind::Markdown, "tasks.md"),
+      "md:".parse().unwrap()
+    );
+    assert_eq!(spec(StoreKind::Json, "my.json"), "my.json".parse().unwrap());
+    assert_eq!(
+      spec(StoreKind::TodoTxt, "~/todo/todo.txt"),
+      "~/todo/todo.txt".parse().unwrap()
+    );
+    assert_eq!(
+      spec(StoreKind::Markdown, "notes.md"),
+      "notes.md".parse().unwrap()
+    );
+    assert_eq!(
+      spec(StoreKind::Directory, "notes/"),
+      "notes/".parse().unwrap()
+    );
+    assert!("xml:tasks.xml".parse::<StoreSpec>().is_err());
+  }
+}
@@ -1,19 +1,72 @@
+use crate::config::Config;
+use crate::notes;
+use crate::tasks::Task;
+use crate::tasks::edit;
+use crate::tasks::filter::Filter;
+use crate::tasks::format::{self, OutputFormat};
 use crate::tasks::io;
+use crate::tasks::quick_add;
 use crate::tasks::tasklist::*;
-use anyhow::Result;
+use crate::tasks::watch;
+use crate::timer;
+use anyhow::{Result, anyhow};
+use chrono::Local;
+use console::{Key, Term};
+use std::cell::RefCell;
+use std::mem;
+use std::path::PathBuf;
 
 pub struct TaskController<S: TaskListPersist> {
   storage: S,
   tasklist: TaskList,
+  is_loaded: bool,
+  config: Config,
+  /// Where the notes `[[links]]` can point to are
+  note_dirs: Vec<PathBuf>,
 }
 
 impl<S: TaskListPersist> TaskController<S> {
-  pub fn new(mut storage: S) -> Result<TaskController<S>> {
-    let tasklist = storage.load_tasklist()?;
-    Ok(TaskController { storage, tasklist })
+  pub fn new(mut storage: S, config: Config, note_dirs: Vec<PathBuf>) -> Result<TaskController<S>> {
+    storage.lock()?;
+    Ok(TaskController {
+      storage,
+      tasklist: TaskList::from(Vec::new()),
+      is_loaded: false,
+      config,
+      note_dirs,
+    })
   }
 
-  pub fn add(&mut self, task_description: String) -> Result<()> {
+  pub fn add(&mut self, input: &str, dry_run: bool) -> Result<()> {
+    let meta = quick_add::parse(input, Local::now().naive_local());
+    let task_description = meta.to_description();
+
+    if dry_run {
+      println!("Description: {}", meta.title);
+      if let Some(due) = meta.due {
+        match meta.due_time {
+          Some(time) => println!("Due:         {} {}", due, time.format("%H:%M")),
+          None => println!("Due:         {}", due),
+        }
+      }
+      if let Some(priority) = meta.priority {
+        println!("Priority:    p{}", priority);
+      }
+      if !meta.tags.is_empty() {
+        println!("Tags:        {}", meta.tags.join(", "));
+      }
+      if let Some(context) = &meta.context {
+        println!("Context:     {}", context);
+      }
+      if let Some(recurrence) = &meta.recurrence {
+        println!("Recurrence:  {}", recurrence);
+      }
+      println!("Task:        {}", task_description);
+
+      return Ok(());
+    }
+
+    self.load()?;
     self.tasklist.add_task(task_description)?;
     self.save()?;
     println!("Task added");
@@ -21,19 +74,169 @@ impl<S: TaskListPersist> TaskController<S> {
     Ok(())
   }
 
-  pub fn list(&mut self) -> Result<()> {
-    let mut console = io::TasksInteract::new(&mut self.tasklist);
-    let should_save = io::TasksInteract::interact(&mut console)?;
+  /// Changes the description of the task `selector` picks
+  pub fn edit(&mut self, selector: &str, description: &str) -> Result<()> {
+    self.load()?;
+    let task = self.tasklist.select(selector)?;
+    let edited = Task {
+      description: description.trim().to_string(),
+      ..task.clone()
+    };
+    edit::apply(&mut self.tasklist, &task, &edited)?;
+    self.save()?;
+    println!("Task updated");
+
+    Ok(())
+  }
+
+  /// Opens the task `selector` picks in the editor along with its notes
+  pub fn edit_in_editor(&mut self, selector: &str) -> Result<()> {
+    self.load()?;
+    let task = self.tasklist.select(selector)?;
+    // not every store keeps notes
+    let notes = self.storage.load_notes(&task.description).ok();
+
+    self.storage.unlock();
+    let edited = edit::edit(&task, notes.as_deref(), |err| {
+      eprintln!("{}", err);
+      let term = Term::stderr();
+      if !term.is_term() {
+        return Ok(false);
+      }
+      term.write_str("Edit again? [y/n] ")?;
+      let key = term.read_key()?;
+      term.write_line("")?;
+      Ok(matches!(key, Key::Char('y') | Key::Enter))
+    });
+    self.storage.lock()?;
+    let Some((edited, new_notes)) = edited? else {
+      println!("Nothing changed");
+      return Ok(());
+    };
+
+    // the list may have changed while the editor was open
+    self.tasklist = self.storage.load_tasklist()?;
+    if !self.tasklist.has_task(&task.description) {
+      return Err(anyhow!(
+        "\"{}\" was changed while it was being edited",
+        task.description
+      ));
+    }
+    edit::apply(&mut self.tasklist, &task, &edited)?;
+    // saved under the description the store has, before it's changed
+    if notes.is_some_and(|notes| notes != new_notes) {
+      self.storage.save_notes(&task.description, &new_notes)?;
+    }
+    self.save()?;
+    println!("Task updated");
+
+    Ok(())
+  }
+
+  pub fn list(
+    &mut self,
+    list_option: GetTasksFilterOption,
+    format: Option<OutputFormat>,
+  ) -> Result<()> {
+    // fall back to plain output when piped so the list can be fed to other tools
+    let format = match format {
+      Some(format) => Some(format),
+      None if !Term::stdout().is_term() => Some(OutputFormat::Plain),
+      None => None,
+    };
+
+    if let Some(format) = format {
+      // only what's listed needs loading as nothing can be changed
+      let tasks = self
+        .storage
+        .load_tasklist_filtered(&list_option)?
+        .get_tasks(&list_option);
+      return format::write_tasks(&mut std::io::stdout().lock(), &tasks, format);
+    }
+
+    self.load()?;
+
+    let watch_path = self.storage.watch_path();
+    // not every store keeps time, and the list works without it
+    let time_entries = self.storage.load_time_entries();
+    let keeps_time = time_entries.is_ok();
+    let note_files = notes::files(&self.note_dirs)?;
+    // other commands can run while the list is open, so the lock is only
+    // taken again to write
+    self.storage.unlock();
+    let should_save = {
+      let storage = RefCell::new(&mut self.storage);
+      let mut console = io::TasksInteract::new(&mut self.tasklist, list_option, &self.config)
+        .with_watch_path(watch_path.clone())
+        .with_time_entries(time_entries.unwrap_or_default())
+        .with_note_files(note_files)
+        .with_reload(|| storage.borrow_mut().load_tasklist())
+        .with_notes(
+          |description| storage.borrow_mut().load_notes(description),
+          |description, notes| {
+            locked(&mut **storage.borrow_mut(), |storage| {
+              storage.save_notes(description, notes)
+            })
+          },
+        );
+      if keeps_time {
+        console = console.with_timer(|description| {
+          locked(&mut **storage.borrow_mut(), |storage| {
+            match description {
+              Some(description) => timer::start_timer(storage, description)?,
+              None => {
+                timer::stop_timer(storage)?;
+              }
+            }
+            storage.load_time_entries()
+          })
+        });
+      }
+      let _watcher = match watch_path {
+        Some(path) => Some(watch::watch(&path, console.file_change_notifier())?),
+        None => None,
+      };
+
+      io::TasksInteract::interact(&mut console)?
+    };
     if should_save {
+      self.storage.lock()?;
+      // keep what was changed elsewhere while the list was open
+      let pending = mem::replace(&mut self.tasklist, self.storage.load_tasklist()?);
+      self.tasklist.rebase(pending);
       self.save()?;
     }
 
     Ok(())
   }
 
+  pub fn resolve_filter(&self, input: &str) -> Result<Filter> {
+    self.config.resolve_filter(input)
+  }
+
+  fn load(&mut self) -> Result<()> {
+    if !self.is_loaded {
+      self.tasklist = self.storage.load_tasklist()?;
+      self.is_loaded = true;
+    }
+
+    Ok(())
+  }
+
   fn save(&mut self) -> Result<()> {
     self.storage.save_tasklist(&mut self.tasklist)?;
 
     Ok(())
   }
 }
+
+/// Runs `f` with the store locked, for writes made while the list is open
+fn locked<S: TaskListPersist, T>(
+  storage: &mut S,
+  f: impl FnOnce(&mut S) -> Result<T>,
+) -> Result<T> {
+  storage.lock()?;
+  let result = f(storage);
+  storage.unlock();
+  result
+}
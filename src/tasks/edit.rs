@@ -0,0 +1,189 @@
+use anyhow::{Result, anyhow};
+use std::env;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use uuid::Uuid;
+
+use crate::editor;
+use crate::tasks::{Task, TaskList, TaskUpdateAction};
+
+/// The task as a checklist item with its notes indented under it
+pub fn to_text(task: &Task, notes: Option<&[String]>) -> String {
+  let mut text = format!("- [{}] {}\n", task.status.marker(), task.description);
+  for note in notes.unwrap_or_default() {
+    if note.trim().is_empty() {
+      text.push('\n');
+    } else {
+      text.push_str(&format!("  {}\n", note));
+    }
+  }
+  text
+}
+
+/// Reads the task and its notes back from edited text. Anything after the
+/// task is an error when the store can't keep notes.
+pub fn parse(text: &str, keeps_notes: bool) -> Result<(Task, Vec<String>)> {
+  let mut lines = text.lines().skip_while(|line| line.trim().is_empty());
+  let first = lines.next().unwrap_or_default();
+  let Some((_, _, status, description)) = TaskList::get_md_captures(first) else {
+    return Err(anyhow!(
+      "The first line should be the task, e.g. \"- [ ] call Bob\""
+    ));
+  };
+  let description = description.trim();
+  if description.is_empty() {
+    return Err(anyhow!("The task needs a description"));
+  }
+
+  let mut notes: Vec<String> = lines
+    .skip_while(|line| line.trim().is_empty())
+    .map(|line| line.trim_end().to_string())
+    .collect();
+  while notes.last().is_some_and(|line| line.is_empty()) {
+    notes.pop();
+  }
+  if let Some((i, _)) = TaskList::md_tasks(&notes).next() {
+    return Err(anyhow!(
+      "Only one task can be edited at a time, but \"{}\" is another",
+      notes[i].trim()
+    ));
+  }
+  if !keeps_notes && !notes.is_empty() {
+    return Err(anyhow!(
+      "This store can't keep notes, so only the task line can be changed"
+    ));
+  }
+
+  // other whitespace is kept as text, so cutting it never splits a character
+  let indent = notes
+    .iter()
+    .filter(|line| !line.is_empty())
+    .map(|line| line.len() - line.trim_start_matches([' ', '\t']).len())
+    .min()
+    .unwrap_or_default();
+  let notes = notes
+    .into_iter()
+    .map(|line| line.get(indent..).unwrap_or_default().to_string())
+    .collect();
+
+  Ok((
+    Task {
+      status,
+      description: description.to_string(),
+    },
+    notes,
+  ))
+}
+
+/// Opens the task and its notes, or just the task when `notes` is `None`,
+/// in the editor as a temp file. When what was written can't be read back,
+/// `retry` is given the error and says whether to open it again. Returns
+/// `None` when nothing was changed or the edit was given up on.
+pub fn edit(
+  task: &Task,
+  notes: Option<&[String]>,
+  mut retry: impl FnMut(&anyhow::Error) -> Result<bool>,
+) -> Result<Option<(Task, Vec<String>)>> {
+  // a name that can't be guessed, and only ever a new file, so nothing put
+  // there beforehand, like a link to another file, is written through
+  let path = env::temp_dir().join(format!("taskmaster-{}.md", Uuid::new_v4()));
+  OpenOptions::new()
+    .write(true)
+    .create_new(true)
+    .open(&path)?
+    .write_all(to_text(task, notes).as_bytes())?;
+
+  // when it's opened again it's with what was written last time
+  let result = loop {
+    if !editor::edit(&path)? {
+      break Err(anyhow!("Set $VISUAL or $EDITOR to edit tasks in an editor"));
+    }
+    let text = fs::read_to_string(&path)?;
+
+    match parse(&text, notes.is_some()) {
+      Ok((edited, new_notes)) => {
+        let unchanged = edited == *task && new_notes == notes.unwrap_or_default();
+        break Ok((!unchanged).then_some((edited, new_notes)));
+      }
+      Err(err) => {
+        if !retry(&err)? {
+          break Ok(None);
+        }
+      }
+    }
+  };
+
+  let _ = fs::remove_file(&path);
+  result
+}
+
+/// Changes `task` in the list to how it was edited
+pub fn apply(tasklist: &mut TaskList, task: &Task, edited: &Task) -> Result<()> {
+  let mut description = task.description.as_str();
+  if edited.description != task.description {
+    if tasklist.has_task(&edited.description) {
+      return Err(anyhow!("Task already exists"));
+    }
+    tasklist.update_task(TaskUpdateAction::Edit(&edited.description), description);
+    description = &edited.description;
+  }
+  if edited.status != task.status {
+    tasklist.update_task(TaskUpdateAction::SetStatus(edited.status), description);
+  }
+
+  Ok(())
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use crate::tasks::TaskStatus;
+
+  fn task(status: TaskStatus, description: &str) -> Task {
+    Task {
+      status,
+      description: String::from(description),
+    }
+  }
+
+  #[test]
+  fn test_round_trip() {
+    let notes = ["see the draft", "", "- [link](spec.md)", "  - nested"].map(String::from);
+    let call = task(TaskStatus::InProgress, "call Bob #sales");
+    let text = to_text(&call, Some(&notes));
+
+    assert_eq!(
+      "- [/] call Bob #sales\n  see the draft\n\n  - [link](spec.md)\n    - nested\n",
+      text
+    );
+    assert_eq!((call, notes.to_vec()), parse(&text, true).unwrap());
+  }
+
+  #[test]
+  fn test_parse() {
+    assert_eq!(
+      (
+        task(TaskStatus::Done, "call Bob"),
+        vec![String::from("done")]
+      ),
+      parse("\n* [x]  call Bob  \n\n\tdone\n\n", true).unwrap()
+    );
+    assert_eq!(
+      (task(TaskStatus::Todo, "call Bob"), Vec::<String>::new()),
+      parse("- [ ] call Bob\n\n", false).unwrap()
+    );
+    assert_eq!(
+      (
+        task(TaskStatus::Todo, "call Bob"),
+        vec![String::from("\u{3000}wide"), String::from("  narrow")]
+      ),
+      parse("- [ ] call Bob\n\u{3000}wide\n  narrow", true).unwrap()
+    );
+
+    let error = |text: &str, keeps_notes: bool| parse(text, keeps_notes).unwrap_err().to_string();
+    assert!(error("call Bob", true).starts_with("The first line"));
+    assert_eq!("The task needs a description", error("- [ ] \t", true));
+    assert!(error("- [ ] call Bob\n  - [ ] email Alice", true).contains("\"- [ ] email Alice\""));
+    assert!(error("- [ ] call Bob\n  a note", false).contains("can't keep notes"));
+  }
+}
@@ -0,0 +1,328 @@
+use crate::tasks::hash_map_task::Task;
+use crate::tasks::meta::TaskMeta;
+use crate::tasks::status::TaskStatus;
+use anyhow::{Result, anyhow};
+use chrono::{Days, Local, NaiveDate};
+use std::{iter::Peekable, vec::IntoIter};
+
+/// Parsed filter expression, e.g. `status:open and (tag:backend or due<today+3d)`
+#[derive(Debug, PartialEq)]
+pub enum Filter {
+  And(Box<Filter>, Box<Filter>),
+  Or(Box<Filter>, Box<Filter>),
+  Not(Box<Filter>),
+  Cond(Condition),
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Condition {
+  /// `status:open` is true for anything not done or cancelled, `status:closed` the opposite
+  Open(bool),
+  Status(TaskStatus),
+  Tag(String),
+  Due(Comparison, DateExpr),
+  Text(String),
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Comparison {
+  Eq,
+  Lt,
+  Le,
+  Gt,
+  Ge,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum DateExpr {
+  Today(i64),
+  Date(NaiveDate),
+}
+
+#[derive(Debug, PartialEq)]
+enum Token {
+  LParen,
+  RParen,
+  Op(String),
+  Word(String),
+  Str(String),
+}
+
+const OP_CHARS: [char; 4] = [':', '~', '<', '>'];
+
+impl Filter {
+  pub fn parse(input: &str) -> Result<Filter> {
+    let mut tokens = tokenize(input)?.into_iter().peekable();
+    if tokens.peek().is_none() {
+      return Err(anyhow!("Filter is empty"));
+    }
+
+    let filter = parse_or(&mut tokens)?;
+    if let Some(token) = tokens.next() {
+      return Err(anyhow!("Unexpected {:?} in filter", token));
+    }
+
+    Ok(filter)
+  }
+
+  pub fn matches(&self, task: &Task) -> bool {
+    self.matches_on(task, Local::now().date_naive())
+  }
+
+  pub fn matches_on(&self, task: &Task, today: NaiveDate) -> bool {
+    match self {
+      Filter::And(a, b) => a.matches_on(task, today) && b.matches_on(task, today),
+      Filter::Or(a, b) => a.matches_on(task, today) || b.matches_on(task, today),
+      Filter::Not(f) => !f.matches_on(task, today),
+      Filter::Cond(cond) => cond.matches_on(task, today),
+    }
+  }
+}
+
+impl Condition {
+  fn matches_on(&self, task: &Task, today: NaiveDate) -> bool {
+    match self {
+      Condition::Open(open) => task.status.is_closed() != *open,
+      Condition::Status(status) => task.status == *status,
+      Condition::Tag(tag) => TaskMeta::parse(&task.description)
+        .tags
+        .iter()
+        .any(|t| t.eq_ignore_ascii_case(tag)),
+      Condition::Due(cmp, expr) => {
+        let Some(due) = TaskMeta::parse(&task.description).due else {
+          return false;
+        };
+        let Some(date) = expr.resolve(today) else {
+          return false;
+        };
+
+        match cmp {
+          Comparison::Eq => due == date,
+          Comparison::Lt => due < date,
+          Comparison::Le => due <= date,
+          Comparison::Gt => due > date,
+          Comparison::Ge => due >= date,
+        }
+      }
+      Condition::Text(text) => task
+        .description
+        .to_lowercase()
+        .contains(&text.to_lowercase()),
+    }
+  }
+}
+
+impl DateExpr {
+  pub fn parse(value: &str) -> Result<DateExpr> {
+    if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+      return Ok(DateExpr::Date(date));
+    }
+
+    let lower = value.to_lowercase();
+    let (base, offset) = match lower.find(['+', '-']) {
+      Some(i) => lower.split_at(i),
+      None => (lower.as_str(), ""),
+    };
+
+    let base_days = match base {
+      "today" => 0,
+      "tomorrow" => 1,
+      "yesterday" => -1,
+      _ => return Err(anyhow!("Invalid date \"{}\"", value)),
+    };
+
+    let days = parse_offset(offset, value)?
+      .checked_add(base_days)
+      .ok_or_else(|| anyhow!("Invalid date \"{}\"", value))?;
+    Ok(DateExpr::Today(days))
+  }
+
+  pub fn resolve(&self, today: NaiveDate) -> Option<NaiveDate> {
+    match self {
+      DateExpr::Date(date) => Some(*date),
+      DateExpr::Today(days) if *days >= 0 => today.checked_add_days(Days::new(*days as u64)),
+      DateExpr::Today(days) => today.checked_sub_days(Days::new(days.unsigned_abs())),
+    }
+  }
+}
+
+// "+3d", "-2w"
+fn parse_offset(offset: &str, value: &str) -> Result<i64> {
+  if offset.is_empty() {
+    return Ok(0);
+  }
+
+  let invalid = || anyhow!("Invalid date offset in \"{}\"", value);
+  let (sign, rest) = offset.split_at(1);
+  let (count, unit) = match rest.char_indices().last() {
+    Some((i, _)) => rest.split_at(i),
+    None => return Err(invalid()),
+  };
+  let count: i64 = count.parse().map_err(|_| invalid())?;
+  let days = match unit {
+    "d" => Some(count),
+    "w" => count.checked_mul(7),
+    _ => return Err(invalid()),
+  };
+  let days = if sign == "-" {
+    days.and_then(i64::checked_neg)
+  } else {
+    days
+  };
+
+  days.ok_or_else(invalid)
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>> {
+  let mut tokens = Vec::new();
+  let mut chars = input.chars().peekable();
+
+  while let Some(&c) = chars.peek() {
+    match c {
+      c if c.is_whitespace() => {
+        chars.next();
+      }
+      '(' => {
+        chars.next();
+        tokens.push(Token::LParen);
+      }
+      ')' => {
+        chars.next();
+        tokens.push(Token::RParen);
+      }
+      '"' => {
+        chars.next();
+        let mut value = String::new();
+        loop {
+          match chars.next() {
+            Some('"') => break,
+            Some(c) => value.push(c),
+            None => return Err(anyhow!("Unterminated string in filter")),
+          }
+        }
+        tokens.push(Token::Str(value));
+      }
+      c if OP_CHARS.contains(&c) => {
+        chars.next();
+        let mut op = c.to_string();
+        if (c == '<' || c == '>') && chars.peek() == Some(&'=') {
+          chars.next();
+          op.push('=');
+        }
+        tokens.push(Token::Op(op));
+      }
+      _ => {
+        let mut word = String::new();
+        while let Some(&c) = chars.peek() {
+          if c.is_whitespace() || "()\"".contains(c) || OP_CHARS.contains(&c) {
+            break;
+          }
+          word.push(c);
+          chars.next();
+        }
+        tokens.push(Token::Word(word));
+      }
+    }
+  }
+
+  Ok(tokens)
+}
+
+type Tokens = Peekable<IntoIter<Token>>;
+
+fn is_keyword(token: Option<&Token>, keyword: &str) -> bool {
+  matches!(token, Some(Token::Word(w)) if w.eq_ignore_ascii_case(keyword))
+}
+
+fn parse_or(tokens: &mut Tokens) -> Result<Filter> {
+  let mut left = parse_and(tokens)?;
+  while is_keyword(tokens.peek(), "or") {
+    tokens.next();
+    let right = parse_and(tokens)?;
+    left = Filter::Or(Box::new(left), Box::new(right));
+  }
+
+  Ok(left)
+}
+
+fn parse_and(tokens: &mut Tokens) -> Result<Filter> {
+  let mut left = parse_unary(tokens)?;
+  loop {
+    if is_keyword(tokens.peek(), "and") {
+      tokens.next();
+    } else if tokens.peek().is_none()
+      || tokens.peek() == Some(&Token::RParen)
+      || is_keyword(tokens.peek(), "or")
+    {
+      break;
+    }
+
+    // terms next to each other are implicitly and'ed
+    let right = parse_unary(tokens)?;
+    left = Filter::And(Box::new(left), Box::new(right));
+  }
+
+  Ok(left)
+}
+
+fn parse_unary(tokens: &mut Tokens) -> Result<Filter> {
+  match tokens.next() {
+    Some(Token::Word(w)) if w.eq_ignore_ascii_case("not") => {
+      Ok(Filter::Not(Box::new(parse_unary(tokens)?)))
+    }
+    Some(Token::LParen) => {
+      let filter = parse_or(tokens)?;
+      match tokens.next() {
+        Some(Token::RParen) => Ok(filter),
+        _ => Err(anyhow!("Missing closing parenthesis in filter")),
+      }
+    }
+    Some(Token::Word(field)) if matches!(tokens.peek(), Some(Token::Op(_))) => {
+      let Some(Token::Op(op)) = tokens.next() else {
+        unreachable!()
+      };
+      let value = match tokens.next() {
+        Some(Token::Word(v)) | Some(Token::Str(v)) => v,
+        _ => return Err(anyhow!("Missing value for \"{}\" in filter", field)),
+      };
+      parse_condition(&field, &op, &value).map(Filter::Cond)
+    }
+    Some(Token::Word(text)) | Some(Token::Str(text)) => Ok(Filter::Cond(Condition::Text(text))),
+    Some(token) => Err(anyhow!("Unexpected {:?} in filter", token)),
+    None => Err(anyhow!("Unexpected end of filter")),
+  }
+}
+
+fn parse_condition(field: &str, op: &str, value: &str) -> Result<Condition> {
+  let invalid_op = || anyhow!("Operator \"{}\" can't be used with \"{}\"", op, field);
+
+  match field.to_lowercase().as_str() {
+    "status" if op == ":" => match value.to_lowercase().as_str() {
+      "open" | "incomplete" => Ok(Condition::Open(true)),
+      "closed" => Ok(Condition::Open(false)),
+      "completed" => Ok(Condition::Status(TaskStatus::Done)),
+      name => TaskStatus::from_name(name)
+        .map(Condition::Status)
+        .ok_or_else(|| anyhow!("Unknown status \"{}\"", value)),
+    },
+    "tag" if op == ":" => Ok(Condition::Tag(value.trim_start_matches('#').to_string())),
+    "due" => {
+      let cmp = match op {
+        ":" => Comparison::Eq,
+        "<" => Comparison::Lt,
+        "<=" => Comparison::Le,
+        ">" => Comparison::Gt,
+        ">=" => Comparison::Ge,
+        _ => return Err(invalid_op()),
+      };
+      Ok(Condition::Due(cmp, DateExpr::parse(value)?))
+    }
+    "text" if op == ":" || op == "~" => Ok(Condition::Text(value.to_string())),
+    "status" | "tag" | "text" => Err(invalid_op()),
+    _ => Err(anyhow!("Unknown filter field \"{}\"", field)),
+  }
+}
+
+#[cfg(test)]
+#[path = "filter_tests.rs"]
+mod filter_tests;
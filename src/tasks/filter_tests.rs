@@ -0,0 +1,132 @@
+#[cfg(test)]
+use super::*;
+
+fn task(description: &str, status: TaskStatus) -> Task {
+  Task {
+    status,
+    description: String::from(description),
+  }
+}
+
+fn today() -> NaiveDate {
+  NaiveDate::from_ymd_opt(2026, 10, 17).unwrap()
+}
+
+#[test]
+fn test_parse_precedence() {
+  let filter = Filter::parse("status:open and (tag:backend or due<today+3d) and text~\"deploy\"");
+
+  let expected = Filter::And(
+    Box::new(Filter::And(
+      Box::new(Filter::Cond(Condition::Open(true))),
+      Box::new(Filter::Or(
+        Box::new(Filter::Cond(Condition::Tag(String::from("backend")))),
+        Box::new(Filter::Cond(Condition::Due(
+          Comparison::Lt,
+          DateExpr::Today(3),
+        ))),
+      )),
+    )),
+    Box::new(Filter::Cond(Condition::Text(String::from("deploy")))),
+  );
+
+  assert_eq!(expected, filter.unwrap());
+}
+
+#[test]
+fn test_parse_errors() {
+  assert!(Filter::parse("").is_err());
+  assert!(Filter::parse("(status:open").is_err());
+  assert!(Filter::parse("colour:red").is_err());
+  assert!(Filter::parse("status<open").is_err());
+  assert!(Filter::parse("due<someday").is_err());
+  assert!(Filter::parse("text~\"deploy").is_err());
+}
+
+#[test]
+fn test_matches() {
+  let filter =
+    Filter::parse("status:open and (tag:backend or due<today+3d) and text~deploy").unwrap();
+
+  assert!(filter.matches_on(&task("Deploy the api #backend", TaskStatus::Todo), today()));
+  assert!(filter.matches_on(
+    &task("deploy docs due:2026-10-19", TaskStatus::Todo),
+    today()
+  ));
+  assert!(!filter.matches_on(
+    &task("deploy docs due:2026-10-20", TaskStatus::Todo),
+    today()
+  ));
+  assert!(!filter.matches_on(&task("deploy the api #backend", TaskStatus::Done), today()));
+  assert!(!filter.matches_on(&task("write the api #backend", TaskStatus::Todo), today()));
+}
+
+#[test]
+fn test_matches_not_and_implicit_and() {
+  let filter = Filter::parse("not tag:backend api").unwrap();
+
+  assert!(filter.matches_on(&task("api docs #frontend", TaskStatus::Todo), today()));
+  assert!(!filter.matches_on(&task("api docs #backend", TaskStatus::Todo), today()));
+  assert!(!filter.matches_on(&task("ui docs", TaskStatus::Todo), today()));
+}
+
+#[test]
+fn test_matches_status_names() {
+  let in_progress = task("write docs", TaskStatus::InProgress);
+  let cancelled = task("write docs", TaskStatus::Cancelled);
+
+  assert!(
+    Filter::parse("status:open")
+      .unwrap()
+      .matches_on(&in_progress, today())
+  );
+  assert!(
+    !Filter::parse("status:open")
+      .unwrap()
+      .matches_on(&cancelled, today())
+  );
+  assert!(
+    Filter::parse("status:closed")
+      .unwrap()
+      .matches_on(&cancelled, today())
+  );
+  assert!(
+    Filter::parse("status:in-progress")
+      .unwrap()
+      .matches_on(&in_progress, today())
+  );
+  assert!(
+    !Filter::parse("status:todo")
+      .unwrap()
+      .matches_on(&in_progress, today())
+  );
+  assert!(Filter::parse("status:someday").is_err());
+}
+
+#[test]
+fn test_date_expressions() {
+  let resolve = |value: &str| DateExpr::parse(value).unwrap().resolve(today()).unwrap();
+
+  assert_eq!(
+    NaiveDate::from_ymd_opt(2026, 10, 18).unwrap(),
+    resolve("tomorrow")
+  );
+  assert_eq!(
+    NaiveDate::from_ymd_opt(2026, 10, 3).unwrap(),
+    resolve("today-2w")
+  );
+  assert_eq!(
+    NaiveDate::from_ymd_opt(2026, 1, 2).unwrap(),
+    resolve("2026-01-02")
+  );
+
+  for value in [
+    "today+3é",
+    "today+é",
+    "today+",
+    "tomorrow+9223372036854775807d",
+    "today+9223372036854775807w",
+  ] {
+    assert!(DateExpr::parse(value).is_err(), "{}", value);
+  }
+}
@@ -0,0 +1,103 @@
+use crate::tasks::hash_map_task::Task;
+use anyhow::Result;
+use clap::ValueEnum;
+use std::io::Write;
+
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+pub enum OutputFormat {
+  Plain,
+  Json,
+  Markdown,
+  Csv,
+}
+
+pub fn write_tasks<W: Write>(out: &mut W, tasks: &[Task], format: OutputFormat) -> Result<()> {
+  match format {
+    OutputFormat::Plain => {
+      for task in tasks {
+        writeln!(out, "[{}] {}", task.status.marker(), task.description)?;
+      }
+    }
+    OutputFormat::Markdown => {
+      for task in tasks {
+        writeln!(out, "- [{}] {}", task.status.marker(), task.description)?;
+      }
+    }
+    OutputFormat::Json => {
+      serde_json::to_writer_pretty(&mut *out, tasks)?;
+      writeln!(out)?;
+    }
+    OutputFormat::Csv => {
+      writeln!(out, "status,description")?;
+      for task in tasks {
+        writeln!(out, "{},{}", task.status, csv_field(&task.description))?;
+      }
+    }
+  }
+
+  Ok(())
+}
+
+pub fn csv_field(value: &str) -> String {
+  if value.contains([',', '"', '\n', '\r']) {
+    format!("\"{}\"", value.replace('"', "\"\""))
+  } else {
+    value.to_string()
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use crate::tasks::TaskStatus;
+
+  fn tasks() -> Vec<Task> {
+    vec![
+      Task {
+        status: TaskStatus::Todo,
+        description: String::from("one"),
+      },
+      Task {
+        status: TaskStatus::Done,
+        description: String::from("two, \"quoted\""),
+      },
+      Task {
+        status: TaskStatus::InProgress,
+        description: String::from("three"),
+      },
+    ]
+  }
+
+  fn render(format: OutputFormat) -> String {
+    let mut out = Vec::new();
+    write_tasks(&mut out, &tasks(), format).unwrap();
+    String::from_utf8(out).unwrap()
+  }
+
+  #[test]
+  fn test_write_plain_and_markdown() {
+    assert_eq!(
+      "[ ] one\n[x] two, \"quoted\"\n[/] three\n",
+      render(OutputFormat::Plain)
+    );
+    assert_eq!(
+      "- [ ] one\n- [x] two, \"quoted\"\n- [/] three\n",
+      render(OutputFormat::Markdown)
+    );
+  }
+
+  #[test]
+  fn test_write_csv_escapes_fields() {
+    assert_eq!(
+      "status,description\ntodo,one\ndone,\"two, \"\"quoted\"\"\"\nin-progress,three\n",
+      render(OutputFormat::Csv)
+    );
+  }
+
+  #[test]
+  fn test_write_json() {
+    let value: serde_json::Value = serde_json::from_str(&render(OutputFormat::Json)).unwrap();
+    assert_eq!(value[0]["description"], "one");
+    assert_eq!(value[1]["status"], "done");
+  }
+}
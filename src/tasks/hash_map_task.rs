@@ -1,8 +1,11 @@
+use serde::Serialize;
+
+use crate::tasks::status::TaskStatus;
 use std::{cmp::Ordering, sync::Arc};
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
 pub struct Task {
-  pub is_completed: bool,
+  pub status: TaskStatus,
   pub description: String,
 }
 
@@ -15,13 +18,13 @@ pub enum HashMapTaskType {
 
 #[derive(Debug, PartialEq)]
 struct OriginalTask {
-  is_completed: bool,
+  status: TaskStatus,
   description: Arc<str>,
 }
 
 #[derive(Debug)]
 pub struct HashMapTask {
-  is_completed: bool,
+  status: TaskStatus,
   description: Arc<str>,
   original: OriginalTask,
   order: usize,
@@ -34,10 +37,10 @@ impl HashMapTask {
     HashMapTask {
       order,
       original: OriginalTask {
-        is_completed: task.is_completed,
+        status: task.status,
         description: desc_arc.clone(),
       },
-      is_completed: task.is_completed,
+      status: task.status,
       description: desc_arc,
       task_type: HashMapTaskType::Existing,
     }
@@ -48,10 +51,10 @@ impl HashMapTask {
     HashMapTask {
       order,
       original: OriginalTask {
-        is_completed: false,
+        status: TaskStatus::Todo,
         description: desc_arc.clone(),
       },
-      is_completed: false,
+      status: TaskStatus::Todo,
       description: desc_arc,
       task_type: HashMapTaskType::Added,
     }
@@ -59,14 +62,14 @@ impl HashMapTask {
 
   pub fn get_task(&self) -> Task {
     Task {
-      is_completed: self.is_completed,
+      status: self.status,
       description: self.description.to_string(),
     }
   }
 
   pub fn get_original_task(&self) -> Task {
     Task {
-      is_completed: self.original.is_completed,
+      status: self.original.status,
       description: self.original.description.to_string(),
     }
   }
@@ -79,8 +82,8 @@ impl HashMapTask {
     self.original.description.clone()
   }
 
-  pub fn toggle(&mut self) {
-    self.is_completed = !self.is_completed;
+  pub fn set_status(&mut self, status: TaskStatus) {
+    self.status = status;
   }
 
   pub fn set_description(&mut self, new_description: &str) -> Arc<str> {
@@ -91,6 +94,23 @@ impl HashMapTask {
   pub fn delete(&mut self) {
     self.task_type = HashMapTaskType::Deleted;
   }
+
+  /// Sets the current state without touching what was originally loaded
+  pub fn apply(&mut self, task: Task) -> Arc<str> {
+    self.status = task.status;
+    self.set_description(&task.description)
+  }
+
+  pub fn set_order(&mut self, order: usize) {
+    self.order = order;
+  }
+
+  /// Discards any changes made since the task was loaded
+  pub fn reset(&mut self) {
+    self.status = self.original.status;
+    self.description = self.original.description.clone();
+    self.task_type = HashMapTaskType::Existing;
+  }
 }
 
 impl Eq for HashMapTask {}
@@ -126,23 +146,23 @@ mod test {
   #[test]
   fn test_task_equality_for_toggle() {
     let mut hmt = HashMapTask::from(Task {
-      is_completed: false,
+      status: TaskStatus::Todo,
       description: "first desc".to_string(),
     }, 0);
 
     assert_eq!(hmt.get_task(), hmt.get_original_task());
 
-    hmt.toggle();
+    hmt.set_status(TaskStatus::Done);
     assert_ne!(hmt.get_task(), hmt.get_original_task());
 
-    hmt.toggle();
+    hmt.set_status(TaskStatus::Todo);
     assert_eq!(hmt.get_task(), hmt.get_original_task());
   }
 
   #[test]
   fn test_task_equality_for_description() {
     let mut hmt = HashMapTask::from(Task {
-      is_completed: false,
+      status: TaskStatus::Todo,
       description: "first desc".to_string(),
     }, 0);
 
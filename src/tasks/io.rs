@@ -1,36 +1,334 @@
+use crate::config::Config;
+use crate::editor;
+use crate::notes::links::{self, Target, Targets};
+use crate::tasks::edit;
 use crate::tasks::hash_map_task::{HashMapTaskType, Task};
-use crate::tasks::{GetTasksFilterOption, TaskList, TaskUpdateAction};
-use anyhow::Result;
+use crate::tasks::watch;
+use crate::tasks::{
+  GetTasksFilterOption, MergeConflict, MergeResolution, TaskList, TaskMeta, TaskStatus,
+  TaskUpdateAction,
+};
+use crate::timer::entry::format_minutes;
+use crate::timer::estimate::progress;
+use crate::timer::{TaskTime, TimeEntry};
+use anyhow::{Result, anyhow};
+use chrono::{DateTime, Local};
 use console::{Key, StyledObject, Term, style};
 use ctrlc;
-use log::debug;
-use std::{fmt::Write as FmtWrite, io::Write as IoWrite, thread, time::Duration};
+use log::{debug, info};
+use std::{
+  collections::HashMap,
+  fmt::Write as FmtWrite,
+  io::{self, Write as IoWrite},
+  mem,
+  path::PathBuf,
+  sync::mpsc::{self, Receiver, RecvTimeoutError, Sender},
+  thread,
+  time::Duration,
+};
 
 #[derive(Clone)]
 enum Mode {
   List,
   Edit(String),
   Add(String),
+  Filter(String),
 }
 
+enum Event {
+  Key(io::Result<Key>),
+  FileChanged,
+  /// Time to redraw the elapsed time of a running timer
+  Tick,
+}
+
+type Reload<'a> = Box<dyn FnMut() -> Result<TaskList> + 'a>;
+/// Starts timing the task with the given description, or stops the timer
+/// with `None`, returning the time entries afterwards
+type Timer<'a> = Box<dyn FnMut(Option<&str>) -> Result<Vec<TaskTime>> + 'a>;
+
+type LoadNotes<'a> = Box<dyn FnMut(&str) -> Result<Vec<String>> + 'a>;
+type SaveNotes<'a> = Box<dyn FnMut(&str, &[String]) -> Result<()> + 'a>;
+
+/// How often the list is redrawn while a timer is running
+const TICK: Duration = Duration::from_secs(10);
+
 pub struct TasksInteract<'a> {
   tasklist: &'a mut TaskList,
   list_option: GetTasksFilterOption,
+  config: &'a Config,
   term: Term,
   height: usize,
   cursor: usize,
   mode: Mode,
+  events_tx: Sender<Event>,
+  events: Receiver<Event>,
+  key_requests: Option<Sender<()>>,
+  awaiting_key: bool,
+  file_changed: bool,
+  reload: Option<Reload<'a>>,
+  banner: Option<String>,
+  /// Time tracked against each task, by description
+  time_entries: HashMap<String, Vec<TimeEntry>>,
+  timer: Option<Timer<'a>>,
+  notes: Option<(LoadNotes<'a>, SaveNotes<'a>)>,
+  /// What `[[links]]` can point to besides tasks
+  note_files: Vec<PathBuf>,
+  /// The file or directory the list is watched through
+  watch_path: Option<PathBuf>,
+  /// What the watched file looked like after the timer or the notes last
+  /// wrote to it, so that write isn't taken for a change made elsewhere
+  own_write: Option<u64>,
 }
 
 impl<'a> TasksInteract<'a> {
-  pub fn new(tasklist: &'a mut TaskList) -> TasksInteract<'a> {
+  pub fn new(
+    tasklist: &'a mut TaskList,
+    list_option: GetTasksFilterOption,
+    config: &'a Config,
+  ) -> TasksInteract<'a> {
+    let (events_tx, events) = mpsc::channel();
     TasksInteract {
       tasklist,
-      list_option: GetTasksFilterOption::All,
+      list_option,
+      config,
       term: Term::stdout(),
       height: 0,
       cursor: 0,
       mode: Mode::List,
+      events_tx,
+      events,
+      key_requests: None,
+      awaiting_key: false,
+      file_changed: false,
+      reload: None,
+      banner: None,
+      time_entries: HashMap::new(),
+      timer: None,
+      notes: None,
+      note_files: Vec::new(),
+      watch_path: None,
+      own_write: None,
+    }
+  }
+
+  /// Used to load the list again when the file it came from changes
+  pub fn with_reload(mut self, reload: impl FnMut() -> Result<TaskList> + 'a) -> TasksInteract<'a> {
+    self.reload = Some(Box::new(reload));
+    self
+  }
+
+  /// Where changes made outside the tool are watched for
+  pub fn with_watch_path(mut self, path: Option<PathBuf>) -> TasksInteract<'a> {
+    self.watch_path = path;
+    self
+  }
+
+  fn fingerprint(&self) -> Option<u64> {
+    self.watch_path.as_deref().map(watch::fingerprint)
+  }
+
+  pub fn with_time_entries(mut self, entries: Vec<TaskTime>) -> TasksInteract<'a> {
+    self.set_time_entries(entries);
+    self
+  }
+
+  /// Used by `t` to start and stop timing tasks
+  pub fn with_timer(
+    mut self,
+    timer: impl FnMut(Option<&str>) -> Result<Vec<TaskTime>> + 'a,
+  ) -> TasksInteract<'a> {
+    self.timer = Some(Box::new(timer));
+    self
+  }
+
+  /// Used by `E` to edit the notes kept with tasks, by the description the
+  /// store has for them
+  pub fn with_notes(
+    mut self,
+    load: impl FnMut(&str) -> Result<Vec<String>> + 'a,
+    save: impl FnMut(&str, &[String]) -> Result<()> + 'a,
+  ) -> TasksInteract<'a> {
+    self.notes = Some((Box::new(load), Box::new(save)));
+    self
+  }
+
+  /// Used by `o` to open the notes tasks link to
+  pub fn with_note_files(mut self, files: Vec<PathBuf>) -> TasksInteract<'a> {
+    self.note_files = files;
+    self
+  }
+
+  fn set_time_entries(&mut self, entries: Vec<TaskTime>) {
+    self.time_entries.clear();
+    for time in entries {
+      self
+        .time_entries
+        .entry(time.task)
+        .or_default()
+        .push(time.entry);
+    }
+  }
+
+  /// When the task's timer was started, if it's running
+  fn running_since(&self, description: &str) -> Option<DateTime<Local>> {
+    self
+      .time_entries
+      .get(description)?
+      .iter()
+      .find(|entry| entry.end.is_none())
+      .map(|entry| entry.start)
+  }
+
+  fn toggle_timer(&mut self, description: &str) {
+    let Some(timer) = self.timer.as_mut() else {
+      self.banner = Some(String::from("this store can't keep track of time"));
+      return;
+    };
+
+    let running = self
+      .time_entries
+      .get(description)
+      .is_some_and(|entries| entries.iter().any(|entry| entry.end.is_none()));
+    match timer((!running).then_some(description)) {
+      Ok(entries) => {
+        self.set_time_entries(entries);
+        self.own_write = self.fingerprint();
+      }
+      Err(err) => self.banner = Some(format!("couldn't start or stop the timer: {}", err)),
+    }
+  }
+
+  /// Opens the task and its notes in the editor. Notes are saved straight
+  /// away while the task itself is changed in the list like any other edit.
+  fn edit_in_editor(&mut self, task: &Task) -> Result<()> {
+    // tasks added since loading aren't in the store to have notes yet
+    let original = self.tasklist.original_description(&task.description);
+    let notes = match (&original, self.notes.as_mut()) {
+      (Some(original), Some((load, _))) => load(original).ok(),
+      _ => None,
+    };
+
+    self.term.clear_last_lines(self.height)?;
+    self.height = 0;
+    self.term.show_cursor()?;
+    let edited = edit::edit(task, notes.as_deref(), |err| {
+      self.term.write_line(&style(err).red().to_string())?;
+      self.height += 2;
+      self.confirm("Edit again?")
+    });
+    let (edited, new_notes) = match edited {
+      Ok(Some(edited)) => edited,
+      Ok(None) => return Ok(()),
+      Err(err) => {
+        self.banner = Some(format!("couldn't edit the task: {}", err));
+        return Ok(());
+      }
+    };
+
+    if let Err(err) = edit::apply(self.tasklist, task, &edited) {
+      self.banner = Some(format!("couldn't edit the task: {}", err));
+      return Ok(());
+    }
+    if let (Some(original), Some((_, save))) = (original, self.notes.as_mut())
+      && notes.is_some_and(|notes| notes != new_notes)
+    {
+      match save(&original, &new_notes) {
+        Ok(()) => self.own_write = self.fingerprint(),
+        Err(err) => self.banner = Some(format!("couldn't save the notes: {}", err)),
+      }
+    }
+
+    Ok(())
+  }
+
+  /// Follows a `[[link]]` in the task or its notes, asking which one when
+  /// there are several. A task is picked out in the list and a note opened
+  /// in the editor.
+  fn follow_link(&mut self, task: &Task) -> Result<()> {
+    let mut names = links::names(&task.description);
+    let original = self.tasklist.original_description(&task.description);
+    if let (Some(original), Some((load, _))) = (original, self.notes.as_mut())
+      && let Ok(notes) = load(&original)
+    {
+      names.extend(notes.iter().flat_map(|note| links::names(note)));
+    }
+    let mut seen = Vec::new();
+    names.retain(|name| {
+      let new = !seen.contains(name);
+      seen.push(name.clone());
+      new
+    });
+
+    let name = match names.len() {
+      0 => {
+        self.banner = Some(String::from("no [[links]] in this task"));
+        return Ok(());
+      }
+      1 => names.remove(0),
+      _ => match self.choose_link(&names)? {
+        Some(name) => name,
+        None => return Ok(()),
+      },
+    };
+
+    let tasks = self.tasklist.get_tasks(&GetTasksFilterOption::All);
+    match Targets::new(&self.note_files, &tasks).resolve(&name) {
+      Some(Target::Task(description)) => {
+        let listed = |tasklist: &TaskList, list_option: &GetTasksFilterOption| {
+          tasklist
+            .get_tasks(list_option)
+            .iter()
+            .position(|task| task.description == description)
+        };
+        // the task may be hidden by a filter
+        if listed(self.tasklist, &self.list_option).is_none() {
+          self.list_option = GetTasksFilterOption::All;
+        }
+        self.cursor = listed(self.tasklist, &self.list_option).unwrap_or(self.cursor);
+      }
+      Some(Target::Note(path)) => {
+        self.term.clear_last_lines(self.height)?;
+        self.height = 0;
+        self.term.show_cursor()?;
+        match editor::edit(&path) {
+          Ok(true) => {}
+          Ok(false) => {
+            self.banner = Some(format!("set $EDITOR to open {}", path.display()));
+          }
+          Err(err) => self.banner = Some(format!("couldn't open {}: {}", path.display(), err)),
+        }
+      }
+      None => self.banner = Some(format!("no note or task is called \"{}\"", name)),
+    }
+
+    Ok(())
+  }
+
+  fn choose_link(&mut self, names: &[String]) -> Result<Option<String>> {
+    // one key picks a link, so only the first nine are offered
+    let names = &names[..names.len().min(9)];
+    for (i, name) in names.iter().enumerate() {
+      self.term.write_line(&format!("{}: [[{}]]", i + 1, name))?;
+    }
+    self.term.write_line("Follow which link?")?;
+    self.height += names.len() + 1;
+
+    let picked = match self.read_key()? {
+      Key::Char(key) => key
+        .to_digit(10)
+        .and_then(|digit| names.get((digit as usize).checked_sub(1)?))
+        .cloned(),
+      _ => None,
+    };
+    Ok(picked)
+  }
+
+  /// Call the returned closure whenever the tasks file changes on disk
+  pub fn file_change_notifier(&self) -> impl Fn() + Send + use<> {
+    let events_tx = self.events_tx.clone();
+    move || {
+      let _ = events_tx.send(Event::FileChanged);
     }
   }
 
@@ -67,16 +365,34 @@ impl<'a> TasksInteract<'a> {
         }
         Mode::Add(entered_val) => self.add_edit_mode(entered_val, false)?,
         Mode::Edit(entered_val) => self.add_edit_mode(entered_val, true)?,
+        Mode::Filter(entered_val) => self.filter_mode(entered_val)?,
       }
     }
   }
 
   fn list_mode(&mut self) -> Result<Option<bool>> {
+    if self.file_changed {
+      self.reload();
+    }
+
     self.term.hide_cursor()?;
     let tasks = &self.tasklist.get_tasks(&self.list_option);
+    if self.cursor >= tasks.len() {
+      self.cursor = tasks.len().saturating_sub(1);
+    }
     self.render_list(tasks)?;
 
-    let key = self.term.read_key()?;
+    let key = match self.next_event()? {
+      Event::Key(key) => {
+        self.banner = None;
+        key?
+      }
+      Event::FileChanged => {
+        self.file_changed = true;
+        return Ok(None);
+      }
+      Event::Tick => return Ok(None),
+    };
 
     debug!("list_mode: {:?}", key);
     match key {
@@ -92,6 +408,7 @@ impl<'a> TasksInteract<'a> {
           self.list_option = GetTasksFilterOption::Completed;
         }
       }
+      Key::Char('d' | 'e' | 'E' | 'o' | 's' | 't' | ' ') if tasks.is_empty() => {}
       Key::Char('d') => {
         self
           .tasklist
@@ -102,6 +419,14 @@ impl<'a> TasksInteract<'a> {
         self.height = 0;
         self.mode = Mode::Edit(tasks[self.cursor].description.clone());
       }
+      Key::Char('E') => {
+        let task = tasks[self.cursor].clone();
+        self.edit_in_editor(&task)?;
+      }
+      Key::Char('o') => {
+        let task = tasks[self.cursor].clone();
+        self.follow_link(&task)?;
+      }
       Key::Char('i') => {
         if let GetTasksFilterOption::Incomplete = self.list_option {
           self.list_option = GetTasksFilterOption::All;
@@ -109,8 +434,13 @@ impl<'a> TasksInteract<'a> {
           self.list_option = GetTasksFilterOption::Incomplete;
         }
       }
+      Key::Char('/') => {
+        self.term.clear_last_lines(self.height)?;
+        self.height = 0;
+        self.mode = Mode::Filter(String::new());
+      }
       Key::Char('j') => {
-        if self.cursor >= self.height - 1 {
+        if self.cursor + 1 >= tasks.len() {
           self.cursor = 0;
         } else {
           self.cursor += 1;
@@ -118,7 +448,7 @@ impl<'a> TasksInteract<'a> {
       }
       Key::Char('k') => {
         if self.cursor == 0 {
-          self.cursor = self.height - 1;
+          self.cursor = tasks.len().saturating_sub(1);
         } else {
           self.cursor -= 1;
         }
@@ -128,6 +458,17 @@ impl<'a> TasksInteract<'a> {
           .tasklist
           .update_task(TaskUpdateAction::Toggle, &tasks[self.cursor].description);
       }
+      Key::Char('s') => {
+        let task = &tasks[self.cursor];
+        self.tasklist.update_task(
+          TaskUpdateAction::SetStatus(task.status.next()),
+          &task.description,
+        );
+      }
+      Key::Char('t') => {
+        let description = tasks[self.cursor].description.clone();
+        self.toggle_timer(&description);
+      }
       Key::Enter => {
         if !self.tasklist.has_changes() {
           debug!("Enter: tasklist has no change");
@@ -165,7 +506,7 @@ impl<'a> TasksInteract<'a> {
     self.term.write_all(output.as_bytes())?;
 
     let tasks = &self.tasklist.get_tasks(&self.list_option);
-    let key = self.term.read_key()?;
+    let key = self.read_key()?;
 
     debug!("add_edit_mode: {:?}", key);
     match key {
@@ -218,16 +559,146 @@ impl<'a> TasksInteract<'a> {
     Ok(())
   }
 
+  fn filter_mode(&mut self, entered_val: String) -> Result<()> {
+    self.term.show_cursor()?;
+    let output = format!("Filter: {}", entered_val);
+    self.term.write_all(output.as_bytes())?;
+
+    let key = self.read_key()?;
+
+    debug!("filter_mode: {:?}", key);
+    match key {
+      Key::Enter => {
+        if entered_val.trim().is_empty() {
+          self.list_option = GetTasksFilterOption::All;
+          self.mode = Mode::List;
+        } else {
+          match self.config.resolve_filter(&entered_val) {
+            Ok(filter) => {
+              self.list_option = GetTasksFilterOption::Query(filter);
+              self.cursor = 0;
+              self.mode = Mode::List;
+            }
+            Err(err) => {
+              self.term.clear_line()?;
+              self.term.write_all(err.to_string().as_bytes())?;
+              thread::sleep(Duration::new(2, 0));
+            }
+          }
+        }
+      }
+      Key::Escape => self.mode = Mode::List,
+      Key::Backspace => {
+        let mut new_val = entered_val.clone();
+        new_val.pop();
+        self.mode = Mode::Filter(new_val);
+      }
+      Key::Char(char) => self.mode = Mode::Filter(format!("{}{}", entered_val, char)),
+      _ => {}
+    }
+    self.term.clear_line()?;
+
+    Ok(())
+  }
+
+  fn next_event(&mut self) -> Result<Event> {
+    // keys are read on another thread so that file changes can be
+    // handled while waiting; only one key is asked for at a time so
+    // nothing is left reading once we're done
+    if !self.awaiting_key {
+      let key_requests = self.key_requests.get_or_insert_with(|| {
+        let (requests_tx, requests) = mpsc::channel::<()>();
+        let events_tx = self.events_tx.clone();
+        thread::spawn(move || {
+          let term = Term::stdout();
+          while requests.recv().is_ok() {
+            if events_tx.send(Event::Key(term.read_key())).is_err() {
+              break;
+            }
+          }
+        });
+        requests_tx
+      });
+      key_requests.send(())?;
+      self.awaiting_key = true;
+    }
+
+    let running = self
+      .time_entries
+      .values()
+      .flatten()
+      .any(|entry| entry.end.is_none());
+    let event = if running {
+      match self.events.recv_timeout(TICK) {
+        Ok(event) => event,
+        Err(RecvTimeoutError::Timeout) => Event::Tick,
+        Err(err) => return Err(err.into()),
+      }
+    } else {
+      self.events.recv()?
+    };
+    if let Event::Key(_) = event {
+      self.awaiting_key = false;
+    }
+
+    Ok(event)
+  }
+
+  /// Waits for a key, leaving any file change to be picked up back in the list
+  fn read_key(&mut self) -> Result<Key> {
+    loop {
+      match self.next_event()? {
+        Event::Key(key) => return Ok(key?),
+        Event::FileChanged => self.file_changed = true,
+        Event::Tick => {}
+      }
+    }
+  }
+
+  fn reload(&mut self) {
+    self.file_changed = false;
+    let Some(reload) = self.reload.as_mut() else {
+      return;
+    };
+
+    // give whatever is writing the file a moment to finish
+    thread::sleep(Duration::from_millis(50));
+
+    match reload() {
+      Ok(fresh) => {
+        info!("tasks file changed on disk; reloaded");
+        let pending = mem::replace(self.tasklist, fresh);
+        self.tasklist.rebase(pending);
+        if self.own_write.is_none() || self.own_write != self.fingerprint() {
+          self.banner = Some(String::from("file changed on disk"));
+        }
+      }
+      Err(err) => {
+        info!("failed to reload tasks file: {}", err);
+        self.banner = Some(format!(
+          "file changed on disk but couldn't be loaded: {}",
+          err
+        ));
+      }
+    }
+  }
+
   fn render_list(&mut self, tasks_to_print: &[Task]) -> Result<()> {
     self.term.clear_last_lines(self.height)?;
 
+    let mut output = String::new();
+    if let Some(banner) = &self.banner {
+      writeln!(&mut output, "{}", style(banner).yellow())?;
+    }
+
     if tasks_to_print.is_empty() {
-      self.term.write_all("No tasks here\n".as_bytes())?;
-      self.height = 1;
+      writeln!(&mut output, "No tasks here")?;
+      self.term.write_all(output.as_bytes())?;
+      self.height = output.lines().count();
       return Ok(());
     }
 
-    let mut output = String::new();
+    let now = Local::now();
     for (i, task) in tasks_to_print.iter().enumerate() {
       if i == self.cursor {
         write!(&mut output, "{}", style("> ").cyan())?;
@@ -235,14 +706,37 @@ impl<'a> TasksInteract<'a> {
         write!(&mut output, "  ")?;
       };
 
-      if task.is_completed {
-        let description = style(task.description.clone()).strikethrough();
-        let task_str = style(format!("● {}", description)).green();
-        writeln!(&mut output, "{}", task_str)?;
-      } else {
-        let task_str = style(format!("○ {}", task.description.clone())).white();
-        writeln!(&mut output, "{}", task_str)?;
+      let task_str = format!(
+        "{} {}",
+        status_dot(task.status),
+        status_desc(task.status, task.description.clone())
+      );
+      write!(
+        &mut output,
+        "{}",
+        status_colour(task.status, style(task_str))
+      )?;
+
+      if let Some(start) = self.running_since(&task.description) {
+        let elapsed = format_minutes((now - start).num_minutes());
+        write!(&mut output, " {}", style(format!("⏱ {}", elapsed)).yellow())?;
+      }
+      let entries = self.time_entries.get(&task.description);
+      let pomodoros = entries.map_or(0, |entries| entries.iter().filter(|e| e.pomodoro).count());
+      if pomodoros > 0 {
+        write!(&mut output, " {}", style(format!("🍅{}", pomodoros)).dim())?;
+      }
+      let logged = entries.map_or(0, |entries| entries.iter().map(|e| e.minutes(now)).sum());
+      let estimate = TaskMeta::parse(&task.description).estimate;
+      if logged > 0 || estimate.is_some() {
+        let time = style(progress(logged, estimate));
+        let time = match estimate {
+          Some(estimate) if logged > estimate => time.red(),
+          _ => time.dim(),
+        };
+        write!(&mut output, " {}", time)?;
       }
+      writeln!(&mut output)?;
     }
     self.term.write_all(output.as_bytes())?;
 
@@ -254,21 +748,8 @@ impl<'a> TasksInteract<'a> {
   fn render_diff(&mut self) -> Result<()> {
     let mut output = String::new();
 
-    let make_dot = |is_completed: bool| {
-      if is_completed {
-        style("●".to_string())
-      } else {
-        style("○".to_string())
-      }
-    };
-
-    let make_desc = |is_completed: bool, description: String| {
-      let mut desc_style = style(description);
-      if is_completed {
-        desc_style = desc_style.strikethrough();
-      }
-      desc_style
-    };
+    let make_dot = |status: TaskStatus| style(status_dot(status));
+    let make_desc = status_desc;
 
     for hmt in self
       .tasklist
@@ -282,22 +763,22 @@ impl<'a> TasksInteract<'a> {
               if has_changed { obj.dim() } else { obj.white() }
             };
 
-          let task_dot = make_dot(task.is_completed);
-          let task_desc = make_desc(task.is_completed, task.description.to_string());
+          let task_dot = make_dot(task.status);
+          let task_desc = make_desc(task.status, task.description.to_string());
 
           let original_task = hmt.get_original_task();
 
           write!(
             &mut output,
             "  {} ",
-            make_coloured(task_dot, task.is_completed == original_task.is_completed)
+            make_coloured(task_dot, task.status == original_task.status)
           )?;
 
           if task.description != original_task.description {
             write!(
               &mut output,
               "{} ",
-              make_desc(task.is_completed, original_task.description.to_string()).dim()
+              make_desc(task.status, original_task.description.to_string()).dim()
             )?;
           }
 
@@ -310,8 +791,8 @@ impl<'a> TasksInteract<'a> {
         HashMapTaskType::Deleted => {
           let task_str = style(format!(
             "{} {}",
-            make_dot(task.is_completed),
-            make_desc(task.is_completed, task.description.to_string())
+            make_dot(task.status),
+            make_desc(task.status, task.description.to_string())
           ))
           .red();
 
@@ -320,8 +801,8 @@ impl<'a> TasksInteract<'a> {
         HashMapTaskType::Added => {
           let task_str = style(format!(
             "{} {}",
-            make_dot(task.is_completed),
-            make_desc(task.is_completed, task.description.to_string())
+            make_dot(task.status),
+            make_desc(task.status, task.description.to_string())
           ))
           .green();
 
@@ -339,10 +820,87 @@ impl<'a> TasksInteract<'a> {
 
   fn confirm(&mut self, prompt: &str) -> Result<bool> {
     self.term.write_line(&format!("{} [y/n]", prompt))?;
-    if let Key::Char('y') | Key::Enter = self.term.read_key()? {
+    if let Key::Char('y') | Key::Enter = self.read_key()? {
       return Ok(true);
     }
 
     Ok(false)
   }
 }
+
+/// The glyph shown in front of a task for its status
+fn status_dot(status: TaskStatus) -> String {
+  match status {
+    TaskStatus::Todo => String::from("○"),
+    TaskStatus::Done => String::from("●"),
+    TaskStatus::Cancelled => String::from("✕"),
+    TaskStatus::InProgress => String::from("◐"),
+    TaskStatus::Deferred => String::from("»"),
+    TaskStatus::Question => String::from("?"),
+    TaskStatus::Other(marker) => format!("[{}]", marker),
+  }
+}
+
+/// Closed tasks are struck through
+fn status_desc(status: TaskStatus, description: String) -> StyledObject<String> {
+  let desc_style = style(description);
+  if status.is_closed() {
+    desc_style.strikethrough()
+  } else {
+    desc_style
+  }
+}
+
+fn status_colour<D>(status: TaskStatus, obj: StyledObject<D>) -> StyledObject<D> {
+  match status {
+    TaskStatus::Done => obj.green(),
+    TaskStatus::Cancelled => obj.dim(),
+    TaskStatus::InProgress => obj.yellow(),
+    TaskStatus::Deferred => obj.blue(),
+    TaskStatus::Question => obj.magenta(),
+    TaskStatus::Todo | TaskStatus::Other(_) => obj.white(),
+  }
+}
+
+/// Asks which side to keep when a task was changed both here and in the
+/// file since it was loaded
+pub fn resolve_conflict(conflict: &MergeConflict) -> Result<MergeResolution> {
+  let term = Term::stdout();
+  if !term.is_term() {
+    return Err(anyhow!(
+      "\"{}\" was changed in the tasks file since it was loaded",
+      conflict.key
+    ));
+  }
+
+  let describe = |task: &Option<Task>, missing: &str| match task {
+    Some(task) => format!(
+      "{} {}",
+      status_dot(task.status),
+      status_desc(task.status, task.description.clone())
+    ),
+    None => style(missing.to_string()).red().to_string(),
+  };
+
+  term.write_line(&format!(
+    "\"{}\" was also changed in the file while you were editing",
+    conflict.key
+  ))?;
+  term.write_line(&format!(
+    "  yours:   {}",
+    describe(&conflict.ours, "deleted")
+  ))?;
+  term.write_line(&format!(
+    "  on disk: {}",
+    describe(&conflict.theirs, "removed")
+  ))?;
+  term.write_line("Keep [y]ours or [d]isk version?")?;
+
+  loop {
+    match term.read_key()? {
+      Key::Char('y') => return Ok(MergeResolution::Ours),
+      Key::Char('d') => return Ok(MergeResolution::Theirs),
+      _ => {}
+    }
+  }
+}
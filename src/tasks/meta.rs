@@ -0,0 +1,422 @@
+use chrono::{NaiveDate, NaiveTime};
+use regex::Regex;
+use std::{fmt::Write, sync::LazyLock};
+
+static TAG_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^#([\w/-]+)$").unwrap());
+static CONTEXT_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^@([\w/-]+)$").unwrap());
+static PRIORITY_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^p([1-4])$").unwrap());
+static DUE_RE: LazyLock<Regex> =
+  LazyLock::new(|| Regex::new(r"^due:(\d{4}-\d{2}-\d{2})(?:T(\d{2}:\d{2}))?$").unwrap());
+static RECURRENCE_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^rec:(\w+)$").unwrap());
+static ESTIMATE_RE: LazyLock<Regex> =
+  LazyLock::new(|| Regex::new(r"^~(?:(\d+)h)?(?:(\d+)m)?$").unwrap());
+static UID_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^uid:(\S+)$").unwrap());
+static DATE_FIELD_RE: LazyLock<Regex> =
+  LazyLock::new(|| Regex::new(r"^(created|done|scheduled|start):(\d{4}-\d{2}-\d{2})$").unwrap());
+
+/// Fields from the Obsidian Tasks plugin, e.g. `📅 2026-10-19`
+const DUE_EMOJI: &str = "📅";
+const SCHEDULED_EMOJI: &str = "⏳";
+const START_EMOJI: &str = "🛫";
+const DONE_EMOJI: &str = "✅";
+const CREATED_EMOJI: &str = "➕";
+const RECURRENCE_EMOJI: &str = "🔁";
+/// From p1 to p4, with the highest (🔺) also read as p1
+const PRIORITY_EMOJI: [&str; 4] = ["⏫", "🔼", "🔽", "⏬"];
+const HIGHEST_EMOJI: &str = "🔺";
+
+/// Drops the variation selector some editors add after an emoji
+fn emoji(word: &str) -> &str {
+  word.trim_end_matches('\u{fe0f}')
+}
+
+fn is_emoji_field(word: &str) -> bool {
+  let word = emoji(word);
+  [
+    DUE_EMOJI,
+    SCHEDULED_EMOJI,
+    START_EMOJI,
+    DONE_EMOJI,
+    CREATED_EMOJI,
+    RECURRENCE_EMOJI,
+    HIGHEST_EMOJI,
+  ]
+  .contains(&word)
+    || PRIORITY_EMOJI.contains(&word)
+}
+
+fn parse_estimate(word: &str) -> Option<i64> {
+  let caps = ESTIMATE_RE.captures(word)?;
+  let number = |i: usize| caps.get(i).and_then(|m| m.as_str().parse::<i64>().ok());
+  if caps.get(1).is_none() && caps.get(2).is_none() {
+    return None;
+  }
+
+  // too big to be meant as an estimate
+  let minutes = number(1)
+    .unwrap_or(0)
+    .checked_mul(60)?
+    .checked_add(number(2).unwrap_or(0))?;
+  (minutes > 0).then_some(minutes)
+}
+
+/// Writes minutes the way estimates are written, e.g. `1h30m`, `2h` or `45m`
+pub fn format_estimate(minutes: i64) -> String {
+  match (minutes / 60, minutes % 60) {
+    (0, minutes) => format!("{}m", minutes),
+    (hours, 0) => format!("{}h", hours),
+    (hours, minutes) => format!("{}h{}m", hours, minutes),
+  }
+}
+
+/// Metadata written inline in a task description, e.g.
+/// `Call Bob due:2026-10-19T15:00 p1 #sales @phone rec:friday`, or with
+/// the Obsidian Tasks emoji, e.g. `Call Bob #sales ⏫ 🔁 every week 📅 2026-10-19`
+#[derive(Debug, Default, PartialEq)]
+pub struct TaskMeta {
+  /// The description with the metadata removed
+  pub title: String,
+  pub due: Option<NaiveDate>,
+  pub due_time: Option<NaiveTime>,
+  pub priority: Option<u8>,
+  pub tags: Vec<String>,
+  pub context: Option<String>,
+  pub recurrence: Option<String>,
+  /// Expected time in minutes, written e.g. `~1h30m`
+  pub estimate: Option<i64>,
+  pub created: Option<NaiveDate>,
+  pub done: Option<NaiveDate>,
+  pub scheduled: Option<NaiveDate>,
+  pub start: Option<NaiveDate>,
+  /// The id the task has in the app it was imported from, e.g.
+  /// `uid:4a3c9e10-…`, so it's matched up again on the next import
+  pub uid: Option<String>,
+  /// Any of the metadata was written with emoji, which `to_description` keeps
+  pub emoji: bool,
+}
+
+impl TaskMeta {
+  pub fn parse(description: &str) -> TaskMeta {
+    let mut meta = TaskMeta::default();
+    let mut title = Vec::new();
+    let words: Vec<&str> = description.split_whitespace().collect();
+
+    let mut i = 0;
+    while i < words.len() {
+      let word = words[i];
+      i += 1;
+
+      if meta.parse_emoji(word, &words, &mut i) {
+        meta.emoji = true;
+      } else if let Some(caps) = TAG_RE.captures(word) {
+        meta.tags.push(caps[1].to_string());
+      } else if let Some(caps) = CONTEXT_RE.captures(word)
+        && meta.context.is_none()
+      {
+        meta.context = Some(caps[1].to_string());
+      } else if let Some(caps) = PRIORITY_RE.captures(word)
+        && meta.priority.is_none()
+      {
+        meta.priority = caps[1].parse().ok();
+      } else if let Some(caps) = DUE_RE.captures(word)
+        && let Ok(due) = NaiveDate::parse_from_str(&caps[1], "%Y-%m-%d")
+      {
+        meta.due = Some(due);
+        meta.due_time = caps
+          .get(2)
+          .and_then(|time| NaiveTime::parse_from_str(time.as_str(), "%H:%M").ok());
+      } else if let Some(caps) = RECURRENCE_RE.captures(word) {
+        meta.recurrence = Some(caps[1].to_string());
+      } else if let Some(estimate) = parse_estimate(word)
+        && meta.estimate.is_none()
+      {
+        meta.estimate = Some(estimate);
+      } else if let Some(caps) = DATE_FIELD_RE.captures(word)
+        && let Ok(date) = NaiveDate::parse_from_str(&caps[2], "%Y-%m-%d")
+      {
+        match &caps[1] {
+          "created" => meta.created = Some(date),
+          "done" => meta.done = Some(date),
+          "scheduled" => meta.scheduled = Some(date),
+          _ => meta.start = Some(date),
+        }
+      } else if let Some(caps) = UID_RE.captures(word)
+        && meta.uid.is_none()
+      {
+        meta.uid = Some(caps[1].to_string());
+      } else {
+        title.push(word);
+      }
+    }
+
+    meta.title = title.join(" ");
+    meta
+  }
+
+  /// Reads an emoji field starting at `word`, moving `i` past any words
+  /// that belong to it
+  fn parse_emoji(&mut self, word: &str, words: &[&str], i: &mut usize) -> bool {
+    let word = emoji(word);
+
+    if let Some(priority) = PRIORITY_EMOJI.iter().position(|p| *p == word) {
+      self.priority = self.priority.or(Some(priority as u8 + 1));
+      return true;
+    }
+    if word == HIGHEST_EMOJI {
+      self.priority = self.priority.or(Some(1));
+      return true;
+    }
+    if word == RECURRENCE_EMOJI {
+      // the rule runs up to the next field, e.g. `🔁 every week on Monday`
+      let rule: Vec<&str> = words[*i..]
+        .iter()
+        .take_while(|word| !is_emoji_field(word) && !TAG_RE.is_match(word))
+        .copied()
+        .collect();
+      if rule.is_empty() {
+        return false;
+      }
+      *i += rule.len();
+      self.recurrence = Some(rule.join(" "));
+      return true;
+    }
+
+    let field = match word {
+      DUE_EMOJI => &mut self.due,
+      SCHEDULED_EMOJI => &mut self.scheduled,
+      START_EMOJI => &mut self.start,
+      DONE_EMOJI => &mut self.done,
+      CREATED_EMOJI => &mut self.created,
+      _ => return false,
+    };
+    let Some(date) = words
+      .get(*i)
+      .and_then(|date| NaiveDate::parse_from_str(date, "%Y-%m-%d").ok())
+    else {
+      return false;
+    };
+    *field = Some(date);
+    *i += 1;
+
+    true
+  }
+
+  /// Writes the title followed by the metadata in its normalised inline form,
+  /// or in the order Obsidian Tasks writes it when it was read from emoji
+  pub fn to_description(&self) -> String {
+    if self.emoji {
+      return self.to_emoji_description();
+    }
+
+    let mut description = self.title.clone();
+
+    if let Some(due) = self.due {
+      write!(description, " due:{}", due.format("%Y-%m-%d")).unwrap();
+      if let Some(time) = self.due_time {
+        write!(description, "T{}", time.format("%H:%M")).unwrap();
+      }
+    }
+    if let Some(priority) = self.priority {
+      write!(description, " p{}", priority).unwrap();
+    }
+    for tag in self.tags.iter() {
+      write!(description, " #{}", tag).unwrap();
+    }
+    if let Some(context) = &self.context {
+      write!(description, " @{}", context).unwrap();
+    }
+    if let Some(recurrence) = &self.recurrence {
+      write!(description, " rec:{}", recurrence).unwrap();
+    }
+    if let Some(estimate) = self.estimate {
+      write!(description, " ~{}", format_estimate(estimate)).unwrap();
+    }
+    let dates = [
+      ("created", self.created),
+      ("done", self.done),
+      ("scheduled", self.scheduled),
+      ("start", self.start),
+    ];
+    for (field, date) in dates {
+      if let Some(date) = date {
+        write!(description, " {}:{}", field, date.format("%Y-%m-%d")).unwrap();
+      }
+    }
+    if let Some(uid) = &self.uid {
+      write!(description, " uid:{}", uid).unwrap();
+    }
+
+    description.trim().to_string()
+  }
+
+  /// Adds today's `✅` date to a task written with emoji when it's
+  /// completed, and removes it when it's reopened, as Obsidian Tasks does.
+  /// Returns `None` when the description doesn't need to change.
+  pub fn mark_done(description: &str, today: Option<NaiveDate>) -> Option<String> {
+    let meta = TaskMeta::parse(description);
+    if !meta.emoji || meta.done.is_some() == today.is_some() {
+      return None;
+    }
+
+    let mut words: Vec<String> = Vec::new();
+    let mut skip = false;
+    for word in description.split_whitespace() {
+      if skip {
+        skip = false;
+      } else if emoji(word) == DONE_EMOJI {
+        skip = true;
+      } else {
+        words.push(word.to_string());
+      }
+    }
+    if let Some(today) = today {
+      words.push(format!("{} {}", DONE_EMOJI, today.format("%Y-%m-%d")));
+    }
+
+    Some(words.join(" "))
+  }
+
+  fn to_emoji_description(&self) -> String {
+    let mut description = self.title.clone();
+
+    for tag in self.tags.iter() {
+      write!(description, " #{}", tag).unwrap();
+    }
+    if let Some(context) = &self.context {
+      write!(description, " @{}", context).unwrap();
+    }
+    // before the emoji, as Obsidian Tasks reads those from the end
+    if let Some(estimate) = self.estimate {
+      write!(description, " ~{}", format_estimate(estimate)).unwrap();
+    }
+    if let Some(uid) = &self.uid {
+      write!(description, " uid:{}", uid).unwrap();
+    }
+    if let Some(priority) = self.priority {
+      write!(description, " {}", PRIORITY_EMOJI[priority as usize - 1]).unwrap();
+    }
+    if let Some(recurrence) = &self.recurrence {
+      write!(description, " {} {}", RECURRENCE_EMOJI, recurrence).unwrap();
+    }
+    let dates = [
+      (CREATED_EMOJI, self.created),
+      (START_EMOJI, self.start),
+      (SCHEDULED_EMOJI, self.scheduled),
+      (DUE_EMOJI, self.due.filter(|_| self.due_time.is_none())),
+      (DONE_EMOJI, self.done),
+    ];
+    for (emoji, date) in dates {
+      if let Some(date) = date {
+        write!(description, " {} {}", emoji, date.format("%Y-%m-%d")).unwrap();
+      }
+    }
+    // there's no emoji for a due time
+    if let (Some(due), Some(time)) = (self.due, self.due_time) {
+      write!(
+        description,
+        " due:{}T{}",
+        due.format("%Y-%m-%d"),
+        time.format("%H:%M")
+      )
+      .unwrap();
+    }
+
+    description.trim().to_string()
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn test_parse_meta() {
+    let meta = TaskMeta::parse("#ops deploy the api #backend due:2026-10-20 issue#4");
+    assert_eq!("deploy the api issue#4", meta.title);
+    assert_eq!(vec!["ops", "backend"], meta.tags);
+    assert_eq!(NaiveDate::from_ymd_opt(2026, 10, 20), meta.due);
+    assert_eq!(None, meta.due_time);
+
+    let meta = TaskMeta::parse("nothing due:soon");
+    assert_eq!("nothing due:soon", meta.title);
+    assert_eq!(None, meta.due);
+  }
+
+  #[test]
+  fn test_parse_obsidian_emoji() {
+    let description =
+      "Water plants #garden ⏫ 🔁 every week on Sunday ➕ 2026-10-01 ⏳ 2026-10-17 📅 2026-10-19";
+    let meta = TaskMeta::parse(description);
+
+    assert_eq!("Water plants", meta.title);
+    assert_eq!(vec!["garden"], meta.tags);
+    assert_eq!(Some(1), meta.priority);
+    assert_eq!(Some(String::from("every week on Sunday")), meta.recurrence);
+    assert_eq!(NaiveDate::from_ymd_opt(2026, 10, 1), meta.created);
+    assert_eq!(NaiveDate::from_ymd_opt(2026, 10, 17), meta.scheduled);
+    assert_eq!(NaiveDate::from_ymd_opt(2026, 10, 19), meta.due);
+    assert!(meta.emoji);
+    assert_eq!(description, meta.to_description());
+
+    // an emoji without a date is just part of the title
+    let meta = TaskMeta::parse("Book flights ✈️ 📅 soon 🔽");
+    assert_eq!("Book flights ✈️ 📅 soon", meta.title);
+    assert_eq!(Some(3), meta.priority);
+  }
+
+  #[test]
+  fn test_mark_done() {
+    let today = NaiveDate::from_ymd_opt(2026, 10, 18);
+
+    assert_eq!(
+      Some(String::from("Pay rent 📅 2026-10-01 ✅ 2026-10-18")),
+      TaskMeta::mark_done("Pay rent 📅 2026-10-01", today)
+    );
+    assert_eq!(
+      Some(String::from("Pay rent 📅 2026-10-01")),
+      TaskMeta::mark_done("Pay rent 📅 2026-10-01 ✅ 2026-10-02", None)
+    );
+    assert_eq!(
+      None,
+      TaskMeta::mark_done("Pay rent 📅 2026-10-01 ✅ 2026-10-02", today)
+    );
+    assert_eq!(None, TaskMeta::mark_done("Pay rent due:2026-10-01", today));
+  }
+
+  #[test]
+  fn test_meta_round_trip() {
+    let description = "Call Bob due:2026-10-19T15:00 p1 #sales @phone rec:friday created:2026-10-01 done:2026-10-18 uid:4a3c";
+    let meta = TaskMeta::parse(description);
+
+    assert_eq!("Call Bob", meta.title);
+    assert_eq!(NaiveTime::from_hms_opt(15, 0, 0), meta.due_time);
+    assert_eq!(Some(1), meta.priority);
+    assert_eq!(Some(String::from("phone")), meta.context);
+    assert_eq!(Some(String::from("friday")), meta.recurrence);
+    assert_eq!(NaiveDate::from_ymd_opt(2026, 10, 1), meta.created);
+    assert_eq!(NaiveDate::from_ymd_opt(2026, 10, 18), meta.done);
+    assert_eq!(Some(String::from("4a3c")), meta.uid);
+    assert_eq!(description, meta.to_description());
+  }
+
+  #[test]
+  fn test_parse_estimate() {
+    let meta = TaskMeta::parse("write spec ~1h30m #api");
+    assert_eq!(Some(90), meta.estimate);
+    assert_eq!("write spec", meta.title);
+    assert_eq!("write spec #api ~1h30m", meta.to_description());
+
+    assert_eq!(Some(45), TaskMeta::parse("~45m").estimate);
+    assert_eq!(None, TaskMeta::parse("about ~ 2h").estimate);
+    assert_eq!(None, TaskMeta::parse("~999999999999999999h").estimate);
+    assert_eq!("2h", format_estimate(120));
+    assert_eq!("0m", format_estimate(0));
+
+    let meta = TaskMeta::parse("Water plants #garden ~20m 📅 2026-10-19");
+    assert_eq!(Some(20), meta.estimate);
+    assert_eq!(
+      "Water plants #garden ~20m 📅 2026-10-19",
+      meta.to_description()
+    );
+  }
+}
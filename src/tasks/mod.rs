@@ -1,9 +1,30 @@
 pub mod tasklist;
-pub use tasklist::{GetTasksFilterOption, TaskList, TaskListPersist, TaskUpdateAction};
+pub use tasklist::{
+  GetTasksFilterOption, MergeConflict, MergeResolution, TaskChange, TaskList, TaskListPersist,
+  TaskUpdateAction,
+};
 
 pub mod controller;
 pub use controller::*;
 
+pub mod edit;
+
+pub mod filter;
+pub use filter::Filter;
+
+pub mod format;
+pub use format::OutputFormat;
+
 pub mod io;
 
+pub mod meta;
+pub use meta::TaskMeta;
+
+pub mod status;
+pub use status::TaskStatus;
+
 mod hash_map_task;
+pub use hash_map_task::Task;
+
+pub mod quick_add;
+mod watch;
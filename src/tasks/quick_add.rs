@@ -0,0 +1,315 @@
+use crate::tasks::filter::DateExpr;
+use crate::tasks::meta::TaskMeta;
+use chrono::{Datelike, Days, NaiveDate, NaiveDateTime, NaiveTime, Weekday};
+
+/// Picks due dates, times, priorities, tags, a context and recurrence out of
+/// free text such as `Call Bob tomorrow 3pm p1 #sales @phone every friday`.
+/// Whatever isn't understood is left as the title.
+pub fn parse(input: &str, now: NaiveDateTime) -> TaskMeta {
+  let today = now.date();
+  let words: Vec<&str> = input.split_whitespace().collect();
+  let mut title = Vec::new();
+  let mut meta = TaskMeta::default();
+
+  let mut i = 0;
+  while i < words.len() {
+    let rest = &words[i..];
+
+    if let Some((consumed, recurrence)) = parse_recurrence(rest) {
+      meta.recurrence = Some(recurrence);
+      i += consumed;
+    } else if let Some((consumed, date)) = parse_date(rest, today) {
+      meta.due = Some(date);
+      i += consumed;
+    } else if let Some((consumed, time)) = parse_time(rest) {
+      meta.due_time = Some(time);
+      i += consumed;
+    } else {
+      // anything already in inline form (#tag, @context, p1, due:, rec:)
+      let inline = TaskMeta::parse(words[i]);
+      if inline.title.is_empty() {
+        meta.tags.extend(inline.tags);
+        meta.context = meta.context.or(inline.context);
+        meta.priority = meta.priority.or(inline.priority);
+        meta.due = inline.due.or(meta.due);
+        meta.due_time = inline.due_time.or(meta.due_time);
+        meta.recurrence = inline.recurrence.or(meta.recurrence);
+        meta.estimate = meta.estimate.or(inline.estimate);
+      } else {
+        title.push(words[i]);
+      }
+      i += 1;
+    }
+  }
+
+  if meta.due.is_none() && meta.due_time.is_some() {
+    meta.due = Some(today);
+  }
+
+  meta.title = title.join(" ");
+  meta
+}
+
+/// Reads when something happened, e.g. `yesterday 14:00`, `2026-10-17 9am`,
+/// `friday at 3pm` for the last Friday, or just `14:00` for today
+pub fn parse_past(input: &str, now: NaiveDateTime) -> Option<NaiveDateTime> {
+  let today = now.date();
+  let words: Vec<&str> = input.split_whitespace().collect();
+  let first = words.first()?.to_lowercase();
+
+  let (consumed, date) = if let Ok(expr) = DateExpr::parse(&first) {
+    (1, expr.resolve(today)?)
+  } else if let Some(weekday) = parse_weekday(&first) {
+    let mut date = today;
+    while date.weekday() != weekday {
+      date = date - Days::new(1);
+    }
+    (1, date)
+  } else {
+    (0, today)
+  };
+
+  match parse_time(words.get(consumed..).filter(|rest| !rest.is_empty())?) {
+    Some((used, time)) if consumed + used == words.len() => Some(date.and_time(time)),
+    _ => None,
+  }
+}
+
+fn parse_recurrence(words: &[&str]) -> Option<(usize, String)> {
+  // bare words like "weekly" are too often part of the title
+  if !words[0].eq_ignore_ascii_case("every") {
+    return None;
+  }
+
+  let unit = words.get(1)?.to_lowercase();
+  if let Some(recurrence) = recurrence_unit(&unit, 1) {
+    return Some((2, recurrence));
+  }
+
+  let count: u32 = unit.parse().ok()?;
+  let unit = words.get(2)?.to_lowercase();
+  recurrence_unit(&unit, count).map(|recurrence| (3, recurrence))
+}
+
+fn recurrence_unit(unit: &str, count: u32) -> Option<String> {
+  let unit = unit.trim_end_matches('s');
+  if count == 1 {
+    match unit {
+      "day" => return Some(String::from("daily")),
+      "week" => return Some(String::from("weekly")),
+      "month" => return Some(String::from("monthly")),
+      "year" => return Some(String::from("yearly")),
+      _ => {}
+    }
+  }
+
+  match unit {
+    "day" => Some(format!("{}d", count)),
+    "week" => Some(format!("{}w", count)),
+    "month" => Some(format!("{}m", count)),
+    "year" => Some(format!("{}y", count)),
+    _ if count == 1 => parse_weekday(unit).map(|day| weekday_name(day).to_string()),
+    _ => None,
+  }
+}
+
+fn parse_date(words: &[&str], today: NaiveDate) -> Option<(usize, NaiveDate)> {
+  let first = words[0].to_lowercase();
+
+  // "on friday", "by tomorrow", "next monday", "due friday"
+  if matches!(first.as_str(), "on" | "by" | "next" | "due")
+    && let Some((consumed, date)) = words
+      .get(1..)
+      .and_then(|rest| parse_date_word(rest, today, true))
+  {
+    return Some((consumed + 1, date));
+  }
+
+  // a bare weekday is too often part of the title, e.g. "plan friday party"
+  parse_date_word(words, today, false)
+}
+
+fn parse_date_word(words: &[&str], today: NaiveDate, weekdays: bool) -> Option<(usize, NaiveDate)> {
+  let word = words.first()?.to_lowercase();
+
+  match word.as_str() {
+    "today" | "tonight" => return Some((1, today)),
+    "tomorrow" => return Some((1, today + Days::new(1))),
+    "in" => {
+      let count: u64 = words.get(1)?.parse().ok()?;
+      let days = match words.get(2)?.to_lowercase().trim_end_matches('s') {
+        "day" => count,
+        "week" => count.checked_mul(7)?,
+        _ => return None,
+      };
+      return Some((3, today.checked_add_days(Days::new(days))?));
+    }
+    _ => {}
+  }
+
+  if let Ok(date) = NaiveDate::parse_from_str(&word, "%Y-%m-%d") {
+    return Some((1, date));
+  }
+
+  let weekday = parse_weekday(&word).filter(|_| weekdays)?;
+  let mut date = today + Days::new(1);
+  while date.weekday() != weekday {
+    date = date + Days::new(1);
+  }
+
+  Some((1, date))
+}
+
+fn parse_time(words: &[&str]) -> Option<(usize, NaiveTime)> {
+  if words[0].eq_ignore_ascii_case("at")
+    && let Some(time) = words.get(1).and_then(|word| parse_time_word(word))
+  {
+    return Some((2, time));
+  }
+
+  parse_time_word(words[0]).map(|time| (1, time))
+}
+
+fn parse_time_word(word: &str) -> Option<NaiveTime> {
+  let word = word.to_lowercase();
+  if word == "noon" {
+    return NaiveTime::from_hms_opt(12, 0, 0);
+  }
+
+  let (clock, offset) = if let Some(clock) = word.strip_suffix("am") {
+    (clock, 0)
+  } else if let Some(clock) = word.strip_suffix("pm") {
+    (clock, 12)
+  } else {
+    // a bare number isn't a time unless it has minutes, e.g. 15:00
+    return NaiveTime::parse_from_str(&word, "%H:%M").ok();
+  };
+
+  let (hour, minute) = match clock.split_once(':') {
+    Some((hour, minute)) => (hour.parse::<u32>().ok()?, minute.parse().ok()?),
+    None => (clock.parse::<u32>().ok()?, 0),
+  };
+  if !(1..=12).contains(&hour) {
+    return None;
+  }
+
+  NaiveTime::from_hms_opt(hour % 12 + offset, minute, 0)
+}
+
+fn parse_weekday(word: &str) -> Option<Weekday> {
+  let day = match word {
+    "mon" | "monday" => Weekday::Mon,
+    "tue" | "tues" | "tuesday" => Weekday::Tue,
+    "wed" | "wednesday" => Weekday::Wed,
+    "thu" | "thurs" | "thursday" => Weekday::Thu,
+    "fri" | "friday" => Weekday::Fri,
+    "sat" | "saturday" => Weekday::Sat,
+    "sun" | "sunday" => Weekday::Sun,
+    _ => return None,
+  };
+
+  Some(day)
+}
+
+fn weekday_name(day: Weekday) -> &'static str {
+  match day {
+    Weekday::Mon => "monday",
+    Weekday::Tue => "tuesday",
+    Weekday::Wed => "wednesday",
+    Weekday::Thu => "thursday",
+    Weekday::Fri => "friday",
+    Weekday::Sat => "saturday",
+    Weekday::Sun => "sunday",
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  // a Saturday
+  fn now() -> NaiveDateTime {
+    NaiveDate::from_ymd_opt(2026, 10, 17)
+      .unwrap()
+      .and_hms_opt(9, 30, 0)
+      .unwrap()
+  }
+
+  #[test]
+  fn test_parse_quick_add() {
+    let meta = parse(
+      "Call Bob tomorrow 3pm p1 #sales @phone every friday ~30m",
+      now(),
+    );
+
+    assert_eq!("Call Bob", meta.title);
+    assert_eq!(NaiveDate::from_ymd_opt(2026, 10, 18), meta.due);
+    assert_eq!(NaiveTime::from_hms_opt(15, 0, 0), meta.due_time);
+    assert_eq!(Some(1), meta.priority);
+    assert_eq!(vec!["sales"], meta.tags);
+    assert_eq!(Some(String::from("phone")), meta.context);
+    assert_eq!(Some(String::from("friday")), meta.recurrence);
+    assert_eq!(Some(30), meta.estimate);
+    assert_eq!(
+      "Call Bob due:2026-10-18T15:00 p1 #sales @phone rec:friday ~30m",
+      meta.to_description()
+    );
+  }
+
+  #[test]
+  fn test_parse_dates_and_times() {
+    let meta = parse("standup on monday at 9:15am", now());
+    assert_eq!("standup", meta.title);
+    assert_eq!(NaiveDate::from_ymd_opt(2026, 10, 19), meta.due);
+    assert_eq!(NaiveTime::from_hms_opt(9, 15, 0), meta.due_time);
+
+    let meta = parse("lunch at noon", now());
+    assert_eq!(NaiveDate::from_ymd_opt(2026, 10, 17), meta.due);
+    assert_eq!(NaiveTime::from_hms_opt(12, 0, 0), meta.due_time);
+
+    let meta = parse("renew passport in 2 weeks", now());
+    assert_eq!(NaiveDate::from_ymd_opt(2026, 10, 31), meta.due);
+
+    let meta = parse("thing in 99999999999 days", now());
+    assert_eq!(None, meta.due);
+    assert_eq!("thing in 99999999999 days", meta.title);
+
+    let meta = parse("pay rent every 2 weeks", now());
+    assert_eq!(Some(String::from("2w")), meta.recurrence);
+  }
+
+  #[test]
+  fn test_parse_past() {
+    let at = |date: u32, hour: u32, minute: u32| {
+      NaiveDate::from_ymd_opt(2026, 10, date)
+        .unwrap()
+        .and_hms_opt(hour, minute, 0)
+    };
+
+    assert_eq!(at(16, 14, 0), parse_past("yesterday 14:00", now()));
+    assert_eq!(at(17, 8, 45), parse_past("8:45", now()));
+    assert_eq!(at(12, 15, 0), parse_past("monday at 3pm", now()));
+    assert_eq!(at(17, 9, 0), parse_past("Saturday 9am", now()));
+    assert_eq!(at(3, 9, 0), parse_past("2026-10-03 9am", now()));
+    assert_eq!(None, parse_past("yesterday", now()));
+    assert_eq!(None, parse_past("yesterday 14:00 sharp", now()));
+  }
+
+  #[test]
+  fn test_leaves_unrecognised_words() {
+    let meta = parse("meet at the office on time in 5 minutes", now());
+    assert_eq!("meet at the office on time in 5 minutes", meta.title);
+    assert_eq!(TaskMeta::parse(&meta.title), meta);
+
+    for input in ["Review weekly report", "Plan friday party", "daily standup"] {
+      let meta = parse(input, now());
+      assert_eq!(input, meta.title);
+      assert_eq!((None, None), (meta.due, meta.recurrence));
+    }
+
+    let meta = parse("Plan party due friday every week", now());
+    assert_eq!("Plan party", meta.title);
+    assert_eq!(NaiveDate::from_ymd_opt(2026, 10, 23), meta.due);
+    assert_eq!(Some(String::from("weekly")), meta.recurrence);
+  }
+}
@@ -0,0 +1,156 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+
+/// The state of a task, written as the marker between the brackets of a
+/// markdown checkbox, e.g. `[/]` for in progress
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum TaskStatus {
+  #[default]
+  Todo,
+  Done,
+  Cancelled,
+  InProgress,
+  Deferred,
+  Question,
+  /// Any other marker, kept as it was
+  Other(char),
+}
+
+/// The order the statuses are cycled through in the interactive list
+const CYCLE: [TaskStatus; 6] = [
+  TaskStatus::Todo,
+  TaskStatus::InProgress,
+  TaskStatus::Done,
+  TaskStatus::Cancelled,
+  TaskStatus::Deferred,
+  TaskStatus::Question,
+];
+
+impl TaskStatus {
+  pub fn from_marker(marker: char) -> TaskStatus {
+    match marker {
+      ' ' => TaskStatus::Todo,
+      'x' | 'X' => TaskStatus::Done,
+      '-' => TaskStatus::Cancelled,
+      '/' => TaskStatus::InProgress,
+      '>' => TaskStatus::Deferred,
+      '?' => TaskStatus::Question,
+      other => TaskStatus::Other(other),
+    }
+  }
+
+  pub fn marker(&self) -> char {
+    match self {
+      TaskStatus::Todo => ' ',
+      TaskStatus::Done => 'x',
+      TaskStatus::Cancelled => '-',
+      TaskStatus::InProgress => '/',
+      TaskStatus::Deferred => '>',
+      TaskStatus::Question => '?',
+      TaskStatus::Other(marker) => *marker,
+    }
+  }
+
+  pub fn name(&self) -> String {
+    match self {
+      TaskStatus::Todo => String::from("todo"),
+      TaskStatus::Done => String::from("done"),
+      TaskStatus::Cancelled => String::from("cancelled"),
+      TaskStatus::InProgress => String::from("in-progress"),
+      TaskStatus::Deferred => String::from("deferred"),
+      TaskStatus::Question => String::from("question"),
+      TaskStatus::Other(marker) => marker.to_string(),
+    }
+  }
+
+  /// Reads a name given by `name`, or a single marker character
+  pub fn from_name(name: &str) -> Option<TaskStatus> {
+    let status = match name {
+      "todo" => TaskStatus::Todo,
+      "done" => TaskStatus::Done,
+      "cancelled" | "canceled" => TaskStatus::Cancelled,
+      "in-progress" | "progress" => TaskStatus::InProgress,
+      "deferred" => TaskStatus::Deferred,
+      "question" => TaskStatus::Question,
+      _ => {
+        let mut chars = name.chars();
+        match (chars.next(), chars.next()) {
+          (Some(marker), None) => TaskStatus::from_marker(marker),
+          _ => return None,
+        }
+      }
+    };
+
+    Some(status)
+  }
+
+  pub fn is_completed(&self) -> bool {
+    *self == TaskStatus::Done
+  }
+
+  /// Done or cancelled, so nothing is left to do
+  pub fn is_closed(&self) -> bool {
+    matches!(self, TaskStatus::Done | TaskStatus::Cancelled)
+  }
+
+  /// Done tasks are reopened and everything else is marked done
+  pub fn toggled(&self) -> TaskStatus {
+    match self {
+      TaskStatus::Done => TaskStatus::Todo,
+      _ => TaskStatus::Done,
+    }
+  }
+
+  /// The next status in the cycle, with unknown markers going back to todo
+  pub fn next(&self) -> TaskStatus {
+    match CYCLE.iter().position(|status| status == self) {
+      Some(i) => CYCLE[(i + 1) % CYCLE.len()],
+      None => TaskStatus::Todo,
+    }
+  }
+}
+
+impl fmt::Display for TaskStatus {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "{}", self.name())
+  }
+}
+
+impl Serialize for TaskStatus {
+  fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&self.name())
+  }
+}
+
+impl<'de> Deserialize<'de> for TaskStatus {
+  fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<TaskStatus, D::Error> {
+    let name = String::deserialize(deserializer)?;
+    TaskStatus::from_name(&name)
+      .ok_or_else(|| serde::de::Error::custom(format!("unknown task status \"{}\"", name)))
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn test_markers_and_names_round_trip() {
+    for marker in [' ', 'x', '-', '/', '>', '?', '!'] {
+      let status = TaskStatus::from_marker(marker);
+      assert_eq!(marker, status.marker());
+      assert_eq!(Some(status), TaskStatus::from_name(&status.name()));
+    }
+    assert_eq!(TaskStatus::Done, TaskStatus::from_marker('X'));
+  }
+
+  #[test]
+  fn test_cycle_visits_every_status() {
+    let mut status = TaskStatus::Todo;
+    for _ in 0..CYCLE.len() {
+      status = status.next();
+    }
+    assert_eq!(TaskStatus::Todo, status);
+    assert_eq!(TaskStatus::Todo, TaskStatus::Other('!').next());
+  }
+}
@@ -1,8 +1,18 @@
+use crate::tasks::filter::Filter;
 use crate::tasks::hash_map_task::{HashMapTask, HashMapTaskType, Task};
+use crate::tasks::meta::TaskMeta;
+use crate::tasks::status::TaskStatus;
+use crate::timer::{TaskTime, TimeEntry};
 use anyhow::{Result, anyhow};
+use chrono::Local;
 use log::{debug, info};
 use regex::Regex;
-use std::{collections::HashMap, mem, sync::Arc};
+use std::{
+  collections::{HashMap, HashSet},
+  mem,
+  path::PathBuf,
+  sync::{Arc, LazyLock},
+};
 
 #[derive(Debug, PartialEq)]
 pub struct TaskList {
@@ -11,8 +21,79 @@ pub struct TaskList {
 }
 
 pub trait TaskListPersist {
+  /// Held until the store is dropped so that concurrent runs can't
+  /// interleave their load and save
+  fn lock(&mut self) -> Result<()> {
+    Ok(())
+  }
+  /// Lets other runs in again, for commands that wait around between saves
+  fn unlock(&mut self) {}
+  /// File or directory to watch for changes made outside the tool
+  fn watch_path(&self) -> Option<PathBuf> {
+    None
+  }
   fn load_tasklist(&mut self) -> Result<TaskList>;
+  /// Loads at least the tasks matching `list_option`, for stores that can
+  /// avoid reading everything
+  fn load_tasklist_filtered(&mut self, _list_option: &GetTasksFilterOption) -> Result<TaskList> {
+    self.load_tasklist()
+  }
   fn save_tasklist(&mut self, tasks: &mut TaskList) -> Result<()>;
+  /// Time tracked against each task, for stores that can keep it
+  fn load_time_entries(&mut self) -> Result<Vec<TaskTime>> {
+    Err(anyhow!("This store can't keep track of time"))
+  }
+  /// Replaces the time entries of the task with `description`
+  fn save_time_entries(&mut self, _description: &str, _entries: &[TimeEntry]) -> Result<()> {
+    Err(anyhow!("This store can't keep track of time"))
+  }
+  /// The lines of notes kept with a task, unindented
+  fn load_notes(&mut self, _description: &str) -> Result<Vec<String>> {
+    Err(anyhow!("This store can't keep notes"))
+  }
+  fn save_notes(&mut self, _description: &str, _notes: &[String]) -> Result<()> {
+    Err(anyhow!("This store can't keep notes"))
+  }
+  /// The notes of every task that has some, by description, read in one go
+  fn load_all_notes(&mut self) -> Result<HashMap<String, Vec<String>>> {
+    Err(anyhow!("This store can't keep notes"))
+  }
+}
+
+impl<T: TaskListPersist + ?Sized> TaskListPersist for Box<T> {
+  fn lock(&mut self) -> Result<()> {
+    (**self).lock()
+  }
+  fn unlock(&mut self) {
+    (**self).unlock()
+  }
+  fn watch_path(&self) -> Option<PathBuf> {
+    (**self).watch_path()
+  }
+  fn load_tasklist(&mut self) -> Result<TaskList> {
+    (**self).load_tasklist()
+  }
+  fn load_tasklist_filtered(&mut self, list_option: &GetTasksFilterOption) -> Result<TaskList> {
+    (**self).load_tasklist_filtered(list_option)
+  }
+  fn save_tasklist(&mut self, tasks: &mut TaskList) -> Result<()> {
+    (**self).save_tasklist(tasks)
+  }
+  fn load_time_entries(&mut self) -> Result<Vec<TaskTime>> {
+    (**self).load_time_entries()
+  }
+  fn save_time_entries(&mut self, description: &str, entries: &[TimeEntry]) -> Result<()> {
+    (**self).save_time_entries(description, entries)
+  }
+  fn load_notes(&mut self, description: &str) -> Result<Vec<String>> {
+    (**self).load_notes(description)
+  }
+  fn save_notes(&mut self, description: &str, notes: &[String]) -> Result<()> {
+    (**self).save_notes(description, notes)
+  }
+  fn load_all_notes(&mut self) -> Result<HashMap<String, Vec<String>>> {
+    (**self).load_all_notes()
+  }
 }
 
 #[derive(PartialEq)]
@@ -21,19 +102,82 @@ pub enum GetTasksFilterOption {
   AllWithDeleted,
   Completed,
   Incomplete,
+  Query(Filter),
+}
+
+/// A task changed here that was also changed or removed in the file since
+/// it was loaded. `None` means deleted here or removed from the file.
+#[derive(Debug, PartialEq)]
+pub struct MergeConflict {
+  pub key: Arc<str>,
+  pub ours: Option<Task>,
+  pub theirs: Option<Task>,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum MergeResolution {
+  Ours,
+  Theirs,
+}
+
+/// A change made since the list was loaded, for stores that don't keep
+/// their own copy of the markdown. Existing tasks are identified by their
+/// description as loaded.
+#[derive(Debug, PartialEq)]
+pub enum TaskChange {
+  Added(Task),
+  Updated { original: String, task: Task },
+  Deleted(String),
 }
 
 pub enum TaskUpdateAction<'a> {
+  /// Marks the task done, or reopens it if it's already done
   Toggle,
+  SetStatus(TaskStatus),
   Delete,
   Edit(&'a str),
 }
 
-const MD_RE: &str = r"-\s\[([\sx])\]\s(.+)";
+/// A checklist item with any CommonMark list marker, e.g. `- [ ]`, `* [x]` or `1. [ ]`.
+/// Lines starting with `>` are block quotes so they never match.
+static MD_RE: LazyLock<Regex> = LazyLock::new(|| {
+  Regex::new(r"^\s*(?<bullet>[-*+]|\d{1,9}[.)])[ \t]+\[(?<marker>.)\][ \t]+(?<description>.+)")
+    .unwrap()
+});
+
+/// Follows fenced code blocks through the lines of a markdown file
+#[derive(Default)]
+pub struct CodeFence {
+  /// The fence character and how many of them opened the block
+  open: Option<(char, usize)>,
+}
+
+impl CodeFence {
+  /// Whether `line`, the next line of the file, is code or a fence
+  pub fn is_code(&mut self, line: &str) -> bool {
+    let trimmed = line.trim();
+    let fence_len = |c: char| trimmed.chars().take_while(|x| *x == c).count();
+
+    match self.open {
+      Some((c, len)) => {
+        if fence_len(c) >= len && trimmed.chars().all(|x| x == c) {
+          self.open = None;
+        }
+        true
+      }
+      None => {
+        self.open = ['`', '~']
+          .into_iter()
+          .map(|c| (c, fence_len(c)))
+          .find(|(_, len)| *len >= 3);
+        self.open.is_some()
+      }
+    }
+  }
+}
 
 impl TaskList {
-  #[cfg(test)]
-  fn from(tasks: Vec<Task>) -> TaskList {
+  pub fn from(tasks: Vec<Task>) -> TaskList {
     let mut tasklist = TaskList {
       tasks: HashMap::new(),
       order_cursor: tasks.len(),
@@ -44,7 +188,6 @@ impl TaskList {
     tasklist
   }
 
-  #[cfg(test)]
   fn set_tasks(&mut self, tasks: Vec<Task>) {
     for (i, task) in tasks.into_iter().enumerate() {
       let hmt = HashMapTask::from(task, i);
@@ -59,20 +202,18 @@ impl TaskList {
       order_cursor: 0,
     };
 
-    for line in md_lines.iter() {
-      if let Some((c, d)) = TaskList::get_md_captures(line)? {
-        let hmt = HashMapTask::from(
-          Task {
-            description: d.trim().to_string(),
-            is_completed: c != " ",
-          },
-          tasklist.order_cursor,
-        );
-
-        debug!("adding from md: {:?}", hmt);
-        tasklist.tasks.insert(hmt.get_key(), hmt);
-        tasklist.order_cursor += 1;
-      }
+    for (_, (_, _, c, d)) in TaskList::md_tasks(md_lines) {
+      let hmt = HashMapTask::from(
+        Task {
+          description: d.trim().to_string(),
+          status: c,
+        },
+        tasklist.order_cursor,
+      );
+
+      debug!("adding from md: {:?}", hmt);
+      tasklist.tasks.insert(hmt.get_key(), hmt);
+      tasklist.order_cursor += 1;
     }
 
     Ok(tasklist)
@@ -82,28 +223,51 @@ impl TaskList {
     let tasks = self.remap_to_original_keys();
     info!("saving tasks to markdown: {:?}", tasks);
 
-    let update_line = |task: Task, line: &mut String| {
+    // only the part from the list marker onwards is rewritten so
+    // indentation before it is kept
+    let update_line = |task: Task, line: &mut String, start: usize, bullet: &str, marker: char| {
       debug!("updating md line for \"{}\"", task.description);
-      let check = if task.is_completed { "x" } else { " " };
-      *line = format!("- [{}] {}", check, task.description);
+      *line = format!(
+        "{}{} [{}] {}",
+        &line[..start],
+        bullet,
+        marker,
+        task.description
+      );
     };
 
     let mut lines_to_remove: Vec<usize> = Vec::new();
+    let mut lines_found: HashSet<String> = HashSet::new();
 
     // Update existing tasks
     debug!("updating existing tasks...");
+    let task_lines: HashSet<usize> = TaskList::md_tasks(md_lines).map(|(i, _)| i).collect();
     for (i, line) in md_lines.iter_mut().enumerate() {
-      let line_slice: &str = line.as_str();
-
-      if let Some((_, description)) = TaskList::get_md_captures(line_slice)? {
-        if let Some(hmt) = tasks.get(description)
-          && hmt.task_type != HashMapTaskType::Deleted
-        {
-          debug!("matched on \"{}\"; writing", &description);
-          update_line(hmt.get_task(), line);
-        } else {
-          lines_to_remove.push(i);
+      if !task_lines.contains(&i) {
+        continue;
+      }
+      if let Some((start, bullet, status, description)) = TaskList::get_md_captures(line) {
+        let bullet = bullet.to_string();
+        let description = description.trim().to_string();
+        match tasks.get(description.as_str()) {
+          Some(hmt) if hmt.task_type == HashMapTaskType::Deleted => lines_to_remove.push(i),
+          Some(hmt) => {
+            // leave untouched lines byte-for-byte as they were
+            let task = hmt.get_task();
+            if task != hmt.get_original_task() {
+              debug!("matched on \"{}\"; writing", description);
+              // an unchanged status keeps its marker, e.g. `[X]`
+              let marker = match MD_RE.captures(line).and_then(|caps| caps.name("marker")) {
+                Some(marker) if task.status == status => marker.as_str().chars().next().unwrap(),
+                _ => task.status.marker(),
+              };
+              update_line(task, line, start, &bullet, marker);
+            }
+          }
+          // added to the file by something else since it was loaded
+          None => debug!("keeping unknown task \"{}\"", description),
         }
+        lines_found.insert(description);
       }
     }
 
@@ -116,18 +280,144 @@ impl TaskList {
     });
 
     // Add new tasks
-    for hmt in tasks.values() {
-      if hmt.task_type == HashMapTaskType::Added {
+    let mut added: Vec<&HashMapTask> = tasks
+      .values()
+      .filter(|hmt| hmt.task_type == HashMapTaskType::Added)
+      .collect();
+    added.sort();
+
+    for hmt in added {
+      if !lines_found.contains(&*hmt.get_original_key()) {
         debug!("adding line \"{}\"", hmt.get_task().description);
         md_lines.push(String::new());
         let last_line = md_lines.last_mut().unwrap();
-        update_line(hmt.get_task(), last_line);
+        let task = hmt.get_task();
+        let marker = task.status.marker();
+        update_line(task, last_line, 0, "-", marker);
       }
     }
 
     Ok(())
   }
 
+  pub fn changes(&self) -> Vec<TaskChange> {
+    let mut changes = Vec::new();
+
+    for hmt in self.get_hash_map_tasks(&GetTasksFilterOption::AllWithDeleted) {
+      let task = hmt.get_task();
+      let original = hmt.get_original_key().to_string();
+
+      match hmt.task_type {
+        HashMapTaskType::Added => changes.push(TaskChange::Added(task)),
+        HashMapTaskType::Deleted => changes.push(TaskChange::Deleted(original)),
+        HashMapTaskType::Existing if task != hmt.get_original_task() => {
+          changes.push(TaskChange::Updated { original, task })
+        }
+        HashMapTaskType::Existing => {}
+      }
+    }
+
+    changes
+  }
+
+  /// Finds the tasks changed here that were also changed in `md_lines`,
+  /// the latest contents of the file
+  pub fn merge_conflicts(&self, md_lines: &[String]) -> Vec<MergeConflict> {
+    let theirs: HashMap<&str, Task> = TaskList::md_tasks(md_lines)
+      .map(|(_, (_, _, c, d))| {
+        let task = Task {
+          description: d.trim().to_string(),
+          status: c,
+        };
+        (d.trim(), task)
+      })
+      .collect();
+
+    let mut conflicts = Vec::new();
+    for hmt in self.get_hash_map_tasks(&GetTasksFilterOption::AllWithDeleted) {
+      let original = hmt.get_original_task();
+      let ours = match hmt.task_type {
+        HashMapTaskType::Added => continue,
+        HashMapTaskType::Deleted => None,
+        HashMapTaskType::Existing if hmt.get_task() == original => continue,
+        HashMapTaskType::Existing => Some(hmt.get_task()),
+      };
+
+      let theirs = theirs.get(original.description.as_str());
+      let is_conflict = match theirs {
+        None => ours.is_some(),
+        Some(task) => *task != original && Some(task) != ours.as_ref(),
+      };
+
+      if is_conflict {
+        conflicts.push(MergeConflict {
+          key: hmt.get_key(),
+          ours,
+          theirs: theirs.cloned(),
+        });
+      }
+    }
+
+    conflicts
+  }
+
+  pub fn resolve_conflict(&mut self, conflict: &MergeConflict, resolution: MergeResolution) {
+    let Some(mut hmt) = self.tasks.remove(&conflict.key) else {
+      return;
+    };
+
+    match (resolution, &conflict.theirs) {
+      (MergeResolution::Theirs, _) => hmt.reset(),
+      // the line has gone from the file so write ours as a new one
+      (MergeResolution::Ours, None) => hmt.task_type = HashMapTaskType::Added,
+      (MergeResolution::Ours, Some(_)) => {}
+    }
+
+    self.tasks.insert(hmt.get_key(), hmt);
+  }
+
+  /// Replays the changes still pending in `pending` on top of this list,
+  /// which has just been loaded again. Where both changed a task the
+  /// pending change wins.
+  pub fn rebase(&mut self, pending: TaskList) {
+    let mut pending: Vec<HashMapTask> = pending.tasks.into_values().collect();
+    pending.sort();
+
+    for mut hmt in pending {
+      let original_key = hmt.get_original_key();
+      let task = hmt.get_task();
+
+      match hmt.task_type {
+        HashMapTaskType::Added => {
+          if !self.tasks.contains_key(&hmt.get_key()) {
+            hmt.set_order(self.order_cursor);
+            self.order_cursor += 1;
+            self.tasks.insert(hmt.get_key(), hmt);
+          }
+        }
+        HashMapTaskType::Deleted => {
+          if let Some(existing) = self.tasks.get_mut(&original_key) {
+            existing.delete();
+          }
+        }
+        HashMapTaskType::Existing if task == hmt.get_original_task() => {}
+        HashMapTaskType::Existing => match self.tasks.remove(&original_key) {
+          Some(mut existing) => {
+            let key = existing.apply(task);
+            self.tasks.insert(key, existing);
+          }
+          // removed from the file so keep the edit as a new task
+          None => {
+            let mut added = HashMapTask::new(task.description.clone(), self.order_cursor);
+            added.apply(task);
+            self.order_cursor += 1;
+            self.tasks.insert(added.get_key(), added);
+          }
+        },
+      }
+    }
+  }
+
   pub fn add_task(&mut self, description: String) -> Result<()> {
     let hmt = HashMapTask::new(description, self.order_cursor);
 
@@ -149,12 +439,12 @@ impl TaskList {
     for hmt in self.tasks.values() {
       match list_option {
         GetTasksFilterOption::Incomplete => {
-          if hmt.task_type != HashMapTaskType::Deleted && !hmt.get_task().is_completed {
+          if hmt.task_type != HashMapTaskType::Deleted && !hmt.get_task().status.is_closed() {
             hmts.push(hmt);
           }
         }
         GetTasksFilterOption::Completed => {
-          if hmt.task_type != HashMapTaskType::Deleted && hmt.get_task().is_completed {
+          if hmt.task_type != HashMapTaskType::Deleted && hmt.get_task().status.is_closed() {
             hmts.push(hmt);
           }
         }
@@ -166,6 +456,11 @@ impl TaskList {
         GetTasksFilterOption::AllWithDeleted => {
           hmts.push(hmt);
         }
+        GetTasksFilterOption::Query(filter) => {
+          if hmt.task_type != HashMapTaskType::Deleted && filter.matches(&hmt.get_task()) {
+            hmts.push(hmt);
+          }
+        }
       }
     }
 
@@ -179,12 +474,67 @@ impl TaskList {
     hmts.into_iter().map(|hmt| hmt.get_task()).collect()
   }
 
+  pub fn get_task(&self, description: &str) -> Option<Task> {
+    self
+      .tasks
+      .get(description)
+      .filter(|hmt| hmt.task_type != HashMapTaskType::Deleted)
+      .map(|hmt| hmt.get_task())
+  }
+
+  /// The description the task had when it was loaded, which is what the
+  /// store knows it by, or `None` if it was added since
+  pub fn original_description(&self, description: &str) -> Option<String> {
+    self
+      .tasks
+      .get(description)
+      .filter(|hmt| hmt.task_type == HashMapTaskType::Existing)
+      .map(|hmt| hmt.get_original_key().to_string())
+  }
+
+  /// Finds a task by its description, or by a part of it, ignoring case,
+  /// that only one task or only one open task has
+  pub fn select(&self, selector: &str) -> Result<Task> {
+    if let Some(task) = self.get_task(selector) {
+      return Ok(task);
+    }
+
+    let needle = selector.to_lowercase();
+    let mut matches: Vec<Task> = self
+      .get_tasks(&GetTasksFilterOption::All)
+      .into_iter()
+      .filter(|task| task.description.to_lowercase().contains(&needle))
+      .collect();
+    if matches.len() > 1 && matches.iter().filter(|t| !t.status.is_closed()).count() == 1 {
+      matches.retain(|task| !task.status.is_closed());
+    }
+
+    match matches.len() {
+      0 => Err(anyhow!("No task matches \"{}\"", selector)),
+      1 => Ok(matches.remove(0)),
+      n => Err(anyhow!(
+        "\"{}\" matches {} tasks: {}",
+        selector,
+        n,
+        matches
+          .iter()
+          .map(|task| format!("\"{}\"", task.description))
+          .collect::<Vec<String>>()
+          .join(", ")
+      )),
+    }
+  }
+
   pub fn update_task(&mut self, action: TaskUpdateAction, description: &str) -> Option<()> {
     if self.tasks.contains_key(description) {
       return match action {
         TaskUpdateAction::Toggle => {
-          let hmt = self.tasks.get_mut(description).unwrap();
-          hmt.toggle();
+          let status = self.tasks[description].get_task().status.toggled();
+          self.set_status(description, status);
+          Some(())
+        }
+        TaskUpdateAction::SetStatus(status) => {
+          self.set_status(description, status);
           Some(())
         }
         TaskUpdateAction::Delete => match self.tasks.get_mut(description) {
@@ -209,6 +559,22 @@ impl TaskList {
     None
   }
 
+  fn set_status(&mut self, description: &str, status: TaskStatus) {
+    let Some(mut hmt) = self.tasks.remove(description) else {
+      return;
+    };
+    hmt.set_status(status);
+
+    // keep the done date of tasks written for Obsidian Tasks in step
+    let today = status.is_completed().then(|| Local::now().date_naive());
+    if let Some(marked) = TaskMeta::mark_done(description, today)
+      && !self.tasks.contains_key(marked.as_str())
+    {
+      hmt.set_description(&marked);
+    }
+    self.tasks.insert(hmt.get_key(), hmt);
+  }
+
   pub fn has_task(&self, description: &str) -> bool {
     self.tasks.contains_key(description)
   }
@@ -223,19 +589,40 @@ impl TaskList {
     false
   }
 
-  fn get_md_captures(haystack: &str) -> Result<Option<(&str, &str)>> {
-    let re = Regex::new(MD_RE)?;
-
-    let mut found = None;
-
-    if let Some(caps) = re.captures(haystack)
-      && re.is_match(haystack)
-      && let (Some(c), Some(d)) = (caps.get(1), caps.get(2))
-    {
-      found = Some((c.as_str(), d.as_str()))
+  /// The start of the list marker, the list marker, the status and the
+  /// description of the task on a line
+  pub fn get_md_captures(haystack: &str) -> Option<(usize, &str, TaskStatus, &str)> {
+    let caps = MD_RE.captures(haystack)?;
+
+    match (
+      caps.name("bullet"),
+      caps.name("marker"),
+      caps.name("description"),
+    ) {
+      (Some(b), Some(c), Some(d)) => {
+        let marker = c.as_str().chars().next()?;
+        let status = match marker {
+          marker if marker.is_whitespace() => TaskStatus::Todo,
+          marker => TaskStatus::from_marker(marker),
+        };
+        Some((b.start(), b.as_str(), status, d.as_str()))
+      }
+      _ => None,
     }
+  }
 
-    Ok(found)
+  /// Each task in `md_lines` with the index of its line, skipping anything
+  /// inside a fenced code block
+  pub fn md_tasks(
+    md_lines: &[String],
+  ) -> impl Iterator<Item = (usize, (usize, &str, TaskStatus, &str))> {
+    let mut fence = CodeFence::default();
+
+    md_lines
+      .iter()
+      .enumerate()
+      .filter(move |(_, line)| !fence.is_code(line))
+      .filter_map(|(i, line)| TaskList::get_md_captures(line).map(|caps| (i, caps)))
   }
 
   fn remap_to_original_keys(&mut self) -> HashMap<Arc<str>, HashMapTask> {
@@ -14,11 +14,11 @@ fn test_from_markdown() {
   let result = TaskList::from_markdown(&test_lines);
   let expected = TaskList::from(vec![
     Task {
-      is_completed: false,
+      status: TaskStatus::Todo,
       description: String::from("incomplete task"),
     },
     Task {
-      is_completed: true,
+      status: TaskStatus::Done,
       description: String::from("complete task"),
     },
   ]);
@@ -26,6 +26,59 @@ fn test_from_markdown() {
   assert_eq!(expected, result.unwrap());
 }
 
+#[test]
+fn test_from_markdown_list_styles() {
+  let test_lines = vec![
+    String::from("* [ ] star"),
+    String::from("+ [x] plus"),
+    String::from("1. [ ] first"),
+    String::from("  2) [ ] second"),
+    String::from("> - [ ] quoted"),
+    String::from("```markdown"),
+    String::from("- [ ] in code"),
+    String::from("~~~"),
+    String::from("```"),
+    String::from("- [ ] after code"),
+    String::from("text - [ ] mid line"),
+  ];
+
+  let tasklist = TaskList::from_markdown(&test_lines).unwrap();
+  let descriptions: Vec<String> = tasklist
+    .get_tasks(&GetTasksFilterOption::All)
+    .into_iter()
+    .map(|task| task.description)
+    .collect();
+  assert_eq!(
+    vec!["star", "plus", "first", "second", "after code"],
+    descriptions
+  );
+}
+
+#[test]
+fn test_to_markdown_keeps_list_markers() {
+  let mut test_lines = vec![
+    String::from("* [ ] star"),
+    String::from("  12. [ ] ordered"),
+    String::from("```"),
+    String::from("* [ ] star"),
+    String::from("```"),
+  ];
+
+  let mut tasklist = TaskList::from_markdown(&test_lines).unwrap();
+  tasklist.update_task(TaskUpdateAction::Toggle, "star");
+  tasklist.update_task(TaskUpdateAction::Edit("renumbered"), "ordered");
+  tasklist.save_to_markdown(&mut test_lines).unwrap();
+
+  let expected = vec![
+    String::from("* [x] star"),
+    String::from("  12. [ ] renumbered"),
+    String::from("```"),
+    String::from("* [ ] star"),
+    String::from("```"),
+  ];
+  assert_eq!(expected, test_lines);
+}
+
 #[test]
 fn test_add_task() {
   let mut tasklist = TaskList::from(Vec::new());
@@ -42,7 +95,7 @@ fn test_add_task() {
   let task = hmt.get_task();
 
   assert_eq!("test description", &*task.description);
-  assert!(task.is_completed == false);
+  assert_eq!(TaskStatus::Todo, task.status);
   assert_eq!(1, tasklist.tasks.len());
 }
 
@@ -50,15 +103,15 @@ fn test_add_task() {
 fn test_list_tasks() {
   let tasklist = TaskList::from(vec![
     Task {
-      is_completed: false,
+      status: TaskStatus::Todo,
       description: String::from("one"),
     },
     Task {
-      is_completed: true,
+      status: TaskStatus::Done,
       description: String::from("two"),
     },
     Task {
-      is_completed: false,
+      status: TaskStatus::Todo,
       description: String::from("three"),
     },
   ]);
@@ -80,11 +133,11 @@ fn test_list_tasks() {
 fn test_to_markdown() {
   let mut tasklist = TaskList::from(vec![
     Task {
-      is_completed: true,
+      status: TaskStatus::Done,
       description: String::from("one"),
     },
     Task {
-      is_completed: false,
+      status: TaskStatus::Todo,
       description: String::from("two"),
     },
   ]);
@@ -119,20 +172,68 @@ fn test_to_markdown() {
   assert_eq!(expected, test_lines);
 }
 
+#[test]
+fn test_extended_markers() {
+  let mut test_lines = vec![
+    String::from("- [X] shouted"),
+    String::from("- [-] cancelled"),
+    String::from("- [/] started"),
+    String::from("- [>] later"),
+    String::from("- [?] unsure"),
+    String::from("- [!] important"),
+  ];
+
+  let mut tasklist = TaskList::from_markdown(&test_lines).unwrap();
+  let statuses: Vec<TaskStatus> = tasklist
+    .get_tasks(&GetTasksFilterOption::All)
+    .iter()
+    .map(|task| task.status)
+    .collect();
+  assert_eq!(
+    vec![
+      TaskStatus::Done,
+      TaskStatus::Cancelled,
+      TaskStatus::InProgress,
+      TaskStatus::Deferred,
+      TaskStatus::Question,
+      TaskStatus::Other('!'),
+    ],
+    statuses
+  );
+  assert_eq!(
+    2,
+    tasklist.get_tasks(&GetTasksFilterOption::Completed).len()
+  );
+
+  tasklist.update_task(TaskUpdateAction::SetStatus(TaskStatus::Done), "started");
+  tasklist.update_task(TaskUpdateAction::Edit("very important"), "important");
+  tasklist.save_to_markdown(&mut test_lines).unwrap();
+
+  let expected = vec![
+    String::from("- [X] shouted"),
+    String::from("- [-] cancelled"),
+    String::from("- [x] started"),
+    String::from("- [>] later"),
+    String::from("- [?] unsure"),
+    String::from("- [!] very important"),
+  ];
+  assert_eq!(expected, test_lines);
+}
+
 #[test]
 fn test_update_task() {
   let mut tasklist = TaskList::from(vec![
     Task {
       description: String::from("task to toggle"),
-      is_completed: true,
+      status: TaskStatus::Done,
     },
     Task {
       description: String::from("task to delete"),
-      is_completed: false,
+      status: TaskStatus::Todo,
     },
     Task {
       description: String::from("task to edit"),
-      is_completed: true,
+      status: TaskStatus::Done,
     },
   ]);
 
@@ -146,3 +247,163 @@ fn test_update_task() {
   tasklist.save_to_markdown(&mut lines).unwrap();
   assert_eq!(vec![String::from("- [ ] task to toggle"),], lines);
 }
+
+#[test]
+fn test_list_tasks_with_query() {
+  let tasklist = TaskList::from(vec![
+    Task {
+      status: TaskStatus::Todo,
+      description: String::from("deploy api #backend"),
+    },
+    Task {
+      status: TaskStatus::Done,
+      description: String::from("deploy ui #frontend"),
+    },
+    Task {
+      status: TaskStatus::Todo,
+      description: String::from("write docs #backend"),
+    },
+  ]);
+
+  let filter = Filter::parse("status:open and tag:backend and text~deploy").unwrap();
+  let tasks = tasklist.get_tasks(&GetTasksFilterOption::Query(filter));
+  assert_eq!(1, tasks.len());
+  assert_eq!("deploy api #backend", &*tasks[0].description);
+}
+
+#[test]
+fn test_merge_with_external_changes() {
+  let original = vec![
+    String::from("- [ ] toggled here"),
+    String::from("- [ ] toggled in both"),
+    String::from("- [ ] edited here, removed there"),
+    String::from("- [ ] untouched"),
+  ];
+  let mut tasklist = TaskList::from_markdown(&original).unwrap();
+
+  tasklist.update_task(TaskUpdateAction::Toggle, "toggled here");
+  tasklist.update_task(TaskUpdateAction::Toggle, "toggled in both");
+  tasklist.update_task(
+    TaskUpdateAction::Edit("edited here"),
+    "edited here, removed there",
+  );
+  tasklist.add_task(String::from("added here")).unwrap();
+
+  let mut theirs = vec![
+    String::from("- [ ] toggled here"),
+    String::from("- [x] toggled in both"),
+    String::from("- [x] untouched"),
+    String::from("- [ ] added there"),
+  ];
+
+  let conflicts = tasklist.merge_conflicts(&theirs);
+  assert_eq!(1, conflicts.len());
+  assert_eq!("edited here", &*conflicts[0].key);
+  assert_eq!(None, conflicts[0].theirs);
+
+  tasklist.resolve_conflict(&conflicts[0], MergeResolution::Ours);
+  tasklist.save_to_markdown(&mut theirs).unwrap();
+
+  let expected = vec![
+    String::from("- [x] toggled here"),
+    String::from("- [x] toggled in both"),
+    String::from("- [x] untouched"),
+    String::from("- [ ] added there"),
+    String::from("- [ ] edited here"),
+    String::from("- [ ] added here"),
+  ];
+  assert_eq!(expected, theirs);
+}
+
+#[test]
+fn test_resolve_conflict_with_theirs() {
+  let mut tasklist = TaskList::from_markdown(&[String::from("- [ ] task")]).unwrap();
+  tasklist.update_task(TaskUpdateAction::Edit("my task"), "task");
+
+  let mut theirs = vec![String::from("- [x] their task")];
+  let conflicts = tasklist.merge_conflicts(&theirs);
+  assert_eq!(1, conflicts.len());
+
+  tasklist.resolve_conflict(&conflicts[0], MergeResolution::Theirs);
+  tasklist.save_to_markdown(&mut theirs).unwrap();
+  assert_eq!(vec![String::from("- [x] their task")], theirs);
+}
+
+#[test]
+fn test_rebase_keeps_pending_changes() {
+  let mut pending = TaskList::from_markdown(&[
+    String::from("- [ ] toggled"),
+    String::from("- [ ] deleted"),
+    String::from("- [ ] edited"),
+    String::from("- [ ] removed on disk"),
+  ])
+  .unwrap();
+  pending.update_task(TaskUpdateAction::Toggle, "toggled");
+  pending.update_task(TaskUpdateAction::Delete, "deleted");
+  pending.update_task(TaskUpdateAction::Edit("edited here"), "edited");
+  pending.update_task(TaskUpdateAction::Toggle, "removed on disk");
+  pending.add_task(String::from("added here")).unwrap();
+
+  let mut lines = vec![
+    String::from("- [ ] new on disk"),
+    String::from("- [ ] toggled"),
+    String::from("- [ ] deleted"),
+    String::from("- [ ] edited"),
+  ];
+  let mut tasklist = TaskList::from_markdown(&lines).unwrap();
+  tasklist.rebase(pending);
+
+  let descriptions: Vec<String> = tasklist
+    .get_tasks(&GetTasksFilterOption::All)
+    .into_iter()
+    .map(|task| task.description)
+    .collect();
+  assert_eq!(
+    vec![
+      "new on disk",
+      "toggled",
+      "edited here",
+      "removed on disk",
+      "added here"
+    ],
+    descriptions
+  );
+
+  tasklist.save_to_markdown(&mut lines).unwrap();
+  assert_eq!(
+    vec![
+      String::from("- [ ] new on disk"),
+      String::from("- [x] toggled"),
+      String::from("- [ ] edited here"),
+      String::from("- [x] removed on disk"),
+      String::from("- [ ] added here"),
+    ],
+    lines
+  );
+}
+
+#[test]
+fn test_select() {
+  let tasklist = TaskList::from(vec![
+    Task {
+      status: TaskStatus::Done,
+      description: String::from("write spec"),
+    },
+    Task {
+      status: TaskStatus::Todo,
+      description: String::from("Write docs"),
+    },
+    Task {
+      status: TaskStatus::Todo,
+      description: String::from("review docs"),
+    },
+  ]);
+
+  assert_eq!(
+    "write spec",
+    tasklist.select("write spec").unwrap().description
+  );
+  assert_eq!("Write docs", tasklist.select("write").unwrap().description);
+  assert!(tasklist.select("docs").is_err());
+  assert!(tasklist.select("deploy").is_err());
+}
@@ -0,0 +1,91 @@
+use anyhow::{Result, anyhow};
+use log::debug;
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::fs;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::path::{Path, PathBuf};
+
+use crate::directory;
+
+/// Calls `on_change` whenever `path` is changed on disk. The file is watched
+/// through its directory so that editors (and our own saves) replacing it
+/// with a rename are still picked up. The watch lasts as long as
+/// the returned watcher. A directory is watched with everything under it
+/// for changes to markdown files.
+pub fn watch(path: &Path, on_change: impl Fn() + Send + 'static) -> Result<RecommendedWatcher> {
+  if path.is_dir() {
+    return watch_dir(path, on_change);
+  }
+
+  let dir = match path.parent() {
+    Some(dir) if !dir.as_os_str().is_empty() => dir.to_path_buf(),
+    _ => PathBuf::from("."),
+  };
+  let file_name = path
+    .file_name()
+    .ok_or_else(|| anyhow!("Can't watch {}", path.display()))?
+    .to_owned();
+
+  let mut watcher = notify::recommended_watcher(move |res: notify::Result<notify::Event>| {
+    let Ok(event) = res else {
+      return;
+    };
+    if matches!(event.kind, EventKind::Access(_)) {
+      return;
+    }
+
+    if event
+      .paths
+      .iter()
+      .any(|p| p.file_name() == Some(file_name.as_os_str()))
+    {
+      debug!("watched file changed: {:?}", event);
+      on_change();
+    }
+  })?;
+  watcher.watch(&dir, RecursiveMode::NonRecursive)?;
+
+  Ok(watcher)
+}
+
+/// A hash of what's at `path`, the file or every markdown file under the
+/// directory, to tell our own saves from changes made outside the tool
+pub fn fingerprint(path: &Path) -> u64 {
+  let mut paths = Vec::new();
+  if path.is_dir() {
+    // a file that can't be read now just counts as changed
+    let _ = directory::scan(path, &mut paths);
+  } else {
+    paths.push(path.to_path_buf());
+  }
+
+  let mut hasher = DefaultHasher::new();
+  for path in paths {
+    path.hash(&mut hasher);
+    fs::read(&path).ok().hash(&mut hasher);
+  }
+  hasher.finish()
+}
+
+fn watch_dir(path: &Path, on_change: impl Fn() + Send + 'static) -> Result<RecommendedWatcher> {
+  let mut watcher = notify::recommended_watcher(move |res: notify::Result<notify::Event>| {
+    let Ok(event) = res else {
+      return;
+    };
+    if matches!(event.kind, EventKind::Access(_)) {
+      return;
+    }
+
+    if event
+      .paths
+      .iter()
+      .any(|p| p.extension().is_some_and(|ext| ext == "md"))
+    {
+      debug!("watched directory changed: {:?}", event);
+      on_change();
+    }
+  })?;
+  watcher.watch(path, RecursiveMode::Recursive)?;
+
+  Ok(watcher)
+}
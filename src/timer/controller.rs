@@ -0,0 +1,418 @@
+use anyhow::{Result, anyhow};
+use chrono::{DateTime, Duration, Local, Timelike};
+use console::{Key, Term, style};
+use log::info;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::thread;
+
+use crate::config::Config;
+use crate::tasks::TaskListPersist;
+use crate::tasks::quick_add;
+use crate::timer::entry::{TaskTime, TimeEntry, format_minutes, parse_duration, to_local};
+use crate::timer::pomodoro::{Phase, Pomodoro};
+
+/// How often the countdown is redrawn
+const TICK: std::time::Duration = std::time::Duration::from_millis(250);
+
+pub struct TimerController<S: TaskListPersist> {
+  storage: S,
+  config: Config,
+}
+
+impl<S: TaskListPersist> TimerController<S> {
+  pub fn new(mut storage: S, config: Config) -> Result<TimerController<S>> {
+    storage.lock()?;
+    Ok(TimerController { storage, config })
+  }
+
+  /// Runs work and break intervals until `q` is pressed, logging the work
+  /// against the task
+  pub fn pomodoro(&mut self, selector: &str) -> Result<()> {
+    let task = self.storage.load_tasklist()?.select(selector)?;
+    // fail before the first interval rather than after it
+    self.storage.load_time_entries()?;
+    // other commands can run while the clock counts down
+    self.storage.unlock();
+
+    let term = Term::stdout();
+    let keys = read_keys(&term);
+    let mut pomodoro = Pomodoro::new(&self.config.pomodoro);
+    if term.is_term() {
+      term.hide_cursor()?;
+    }
+
+    loop {
+      let start = Local::now();
+      let end = start + pomodoro.length();
+      if !term.is_term() {
+        // the countdown is only drawn in a terminal
+        term.write_line(&format!("{}: {}", pomodoro.label(), task.description))?;
+      }
+
+      let stopped = loop {
+        let left = end - Local::now();
+        if left <= Duration::zero() {
+          break false;
+        }
+
+        let clock = format!("{:02}:{:02}", left.num_minutes(), left.num_seconds() % 60);
+        let line = format!(
+          "{} {:<10} {}  {}  {}",
+          style("🍅").red(),
+          pomodoro.label(),
+          style(clock).bold(),
+          task.description,
+          style(format!("{} done, q to stop", pomodoro.completed)).dim()
+        );
+        if term.is_term() {
+          term.clear_line()?;
+          term.write_str(&line)?;
+        }
+
+        match keys.as_ref().map(|keys| keys.recv_timeout(TICK)) {
+          Some(Ok(Key::Char('q') | Key::Escape) | Err(RecvTimeoutError::Disconnected)) => {
+            break true;
+          }
+          Some(_) => {}
+          None => thread::sleep(TICK),
+        }
+      };
+
+      if pomodoro.phase == Phase::Work {
+        // a stopped interval still counts as time, just not as a pomodoro
+        let entry = TimeEntry {
+          start,
+          end: Some(Local::now()),
+          pomodoro: !stopped,
+        };
+        self.storage.lock()?;
+        add_time_entry(&mut self.storage, &task.description, entry)?;
+        self.storage.unlock();
+      }
+
+      if stopped {
+        if term.is_term() {
+          term.clear_line()?;
+          term.show_cursor()?;
+        }
+        println!(
+          "{} pomodoros done for \"{}\"",
+          pomodoro.completed, task.description
+        );
+        return Ok(());
+      }
+
+      // ring the bell between intervals
+      term.write_str("\x07")?;
+      pomodoro.advance();
+    }
+  }
+
+  /// Adds time spent on a task after the fact. It ends now unless `at` says
+  /// when it started.
+  pub fn log(&mut self, selector: &str, duration: &str, at: Option<&str>) -> Result<()> {
+    let description = self.storage.load_tasklist()?.select(selector)?.description;
+    let duration = parse_duration(duration)?;
+    let now = this_minute();
+    let start = match at {
+      Some(at) => parse_when(at, now)?,
+      None => now.checked_sub_signed(duration).ok_or_else(too_long)?,
+    };
+    let entry = TimeEntry {
+      start,
+      end: Some(start.checked_add_signed(duration).ok_or_else(too_long)?),
+      pomodoro: false,
+    };
+
+    let (mut entries, others) = self.split_entries(&description)?;
+    check_entry(&entry, &entries, &others, now)?;
+    println!("Logged {} for \"{}\"", entry, description);
+    entries.push(entry);
+    self.save_entries(&description, entries)
+  }
+
+  pub fn start(&mut self, selector: &str) -> Result<()> {
+    let description = self.storage.load_tasklist()?.select(selector)?.description;
+    for time in stop_timer(&mut self.storage)? {
+      print_stopped(&time);
+    }
+    start_timer(&mut self.storage, &description)?;
+    println!("Timing \"{}\"", description);
+    Ok(())
+  }
+
+  pub fn stop(&mut self) -> Result<()> {
+    let stopped = stop_timer(&mut self.storage)?;
+    if stopped.is_empty() {
+      println!("No timer is running");
+    }
+    for time in stopped {
+      print_stopped(&time);
+    }
+    Ok(())
+  }
+
+  /// Prints the task's time entries, numbered for `edit` and `rm`
+  pub fn entries(&mut self, selector: &str) -> Result<()> {
+    let description = self.storage.load_tasklist()?.select(selector)?.description;
+    let (entries, _) = self.split_entries(&description)?;
+    if entries.is_empty() {
+      println!("No time logged for \"{}\"", description);
+      return Ok(());
+    }
+
+    let now = Local::now();
+    for (i, entry) in entries.iter().enumerate() {
+      println!(
+        "{:>3}  {:<28}  {:>6}",
+        i + 1,
+        entry.to_string(),
+        format_minutes(entry.minutes(now))
+      );
+    }
+
+    Ok(())
+  }
+
+  /// Moves one of the task's entries to start `at`, keeping its length, or
+  /// gives it a new length
+  pub fn edit(
+    &mut self,
+    selector: &str,
+    number: usize,
+    at: Option<&str>,
+    duration: Option<&str>,
+  ) -> Result<()> {
+    let description = self.storage.load_tasklist()?.select(selector)?.description;
+    let (mut entries, others) = self.split_entries(&description)?;
+    let mut entry = take_entry(&mut entries, number, &description)?;
+    let now = this_minute();
+
+    if let Some(at) = at {
+      let start = parse_when(at, now)?;
+      entry.end = entry
+        .end
+        .map(|end| {
+          end
+            .checked_add_signed(start - entry.start)
+            .ok_or_else(too_long)
+        })
+        .transpose()?;
+      entry.start = start;
+    }
+    if let Some(duration) = duration {
+      let duration = parse_duration(duration)?;
+      entry.end = Some(
+        entry
+          .start
+          .checked_add_signed(duration)
+          .ok_or_else(too_long)?,
+      );
+    }
+
+    check_entry(&entry, &entries, &others, now)?;
+    println!("Changed to {} for \"{}\"", entry, description);
+    entries.push(entry);
+    self.save_entries(&description, entries)
+  }
+
+  /// Removes one of the task's entries
+  pub fn remove(&mut self, selector: &str, number: usize) -> Result<()> {
+    let description = self.storage.load_tasklist()?.select(selector)?.description;
+    let (mut entries, _) = self.split_entries(&description)?;
+    let entry = take_entry(&mut entries, number, &description)?;
+
+    println!("Removed {} from \"{}\"", entry, description);
+    self.save_entries(&description, entries)
+  }
+
+  /// The task's entries by start time, and every other task's
+  fn split_entries(&mut self, description: &str) -> Result<(Vec<TimeEntry>, Vec<TaskTime>)> {
+    let (entries, others): (Vec<TaskTime>, Vec<TaskTime>) = self
+      .storage
+      .load_time_entries()?
+      .into_iter()
+      .partition(|time| time.task == description);
+
+    let mut entries: Vec<TimeEntry> = entries.into_iter().map(|time| time.entry).collect();
+    entries.sort_by_key(|entry| entry.start);
+    Ok((entries, others))
+  }
+
+  fn save_entries(&mut self, description: &str, mut entries: Vec<TimeEntry>) -> Result<()> {
+    entries.sort_by_key(|entry| entry.start);
+    self.storage.save_time_entries(description, &entries)
+  }
+}
+
+fn print_stopped(time: &TaskTime) {
+  match time.entry.minutes(Local::now()) {
+    0 => println!(
+      "Stopped \"{}\" within a minute, so nothing was logged",
+      time.task
+    ),
+    minutes => println!(
+      "Stopped \"{}\" after {}",
+      time.task,
+      format_minutes(minutes)
+    ),
+  }
+}
+
+/// Entries are written to the minute, so manual ones start on one
+fn this_minute() -> DateTime<Local> {
+  let now = Local::now();
+  now
+    .with_second(0)
+    .and_then(|now| now.with_nanosecond(0))
+    .unwrap_or(now)
+}
+
+fn parse_when(text: &str, now: DateTime<Local>) -> Result<DateTime<Local>> {
+  let time = quick_add::parse_past(text, now.naive_local())
+    .ok_or_else(|| anyhow!("Invalid time \"{}\", e.g. \"yesterday 14:00\"", text))?;
+  to_local(time)
+}
+
+/// For durations that parse but put the entry outside the dates that can be kept
+fn too_long() -> anyhow::Error {
+  anyhow!("Invalid duration, it's too long")
+}
+
+fn take_entry(entries: &mut Vec<TimeEntry>, number: usize, description: &str) -> Result<TimeEntry> {
+  if number == 0 || number > entries.len() {
+    return Err(anyhow!(
+      "\"{}\" has no time entry {}, see `timer log` for its entries",
+      description,
+      number
+    ));
+  }
+
+  Ok(entries.remove(number - 1))
+}
+
+/// Time can't be logged ahead of now, or twice over
+fn check_entry(
+  entry: &TimeEntry,
+  entries: &[TimeEntry],
+  others: &[TaskTime],
+  now: DateTime<Local>,
+) -> Result<()> {
+  if entry.end.unwrap_or(entry.start) > Local::now() {
+    return Err(anyhow!("{} ends in the future", entry));
+  }
+
+  if let Some(other) = entries.iter().find(|other| entry.overlaps(other, now)) {
+    return Err(anyhow!("{} overlaps {} of the same task", entry, other));
+  }
+  if let Some(other) = others
+    .iter()
+    .find(|other| entry.overlaps(&other.entry, now))
+  {
+    return Err(anyhow!(
+      "{} overlaps {} of \"{}\"",
+      entry,
+      other.entry,
+      other.task
+    ));
+  }
+
+  Ok(())
+}
+
+fn add_time_entry(
+  storage: &mut impl TaskListPersist,
+  description: &str,
+  entry: TimeEntry,
+) -> Result<()> {
+  // the minute is the smallest unit some stores keep
+  if entry
+    .end
+    .is_some_and(|end| end - entry.start < Duration::minutes(1))
+  {
+    info!("not logging under a minute for \"{}\"", description);
+    return Ok(());
+  }
+
+  let mut entries: Vec<TimeEntry> = storage
+    .load_time_entries()?
+    .into_iter()
+    .filter(|time| time.task == description)
+    .map(|time| time.entry)
+    .collect();
+  info!("logging {} for \"{}\"", entry, description);
+  entries.push(entry);
+
+  storage.save_time_entries(description, &entries)
+}
+
+/// Starts timing the task with an entry that has no end yet, stopping
+/// whatever else was being timed
+pub fn start_timer(storage: &mut impl TaskListPersist, description: &str) -> Result<()> {
+  stop_timer(storage)?;
+  let entry = TimeEntry {
+    start: Local::now(),
+    end: None,
+    pomodoro: false,
+  };
+  add_time_entry(storage, description, entry)
+}
+
+/// Ends every running entry, returning them as they were stopped. Entries
+/// stopped within a minute of starting are dropped.
+pub fn stop_timer(storage: &mut impl TaskListPersist) -> Result<Vec<TaskTime>> {
+  let now = Local::now();
+  let all = storage.load_time_entries()?;
+  let mut stopped: Vec<TaskTime> = all
+    .iter()
+    .filter(|time| time.entry.end.is_none())
+    .cloned()
+    .collect();
+  let mut descriptions: Vec<String> = stopped.iter().map(|time| time.task.clone()).collect();
+  descriptions.sort();
+  descriptions.dedup();
+
+  for description in descriptions {
+    let mut entries = Vec::new();
+    for time in all.iter().filter(|time| time.task == description) {
+      let mut entry = time.entry.clone();
+      if entry.end.is_none() {
+        if entry.minutes(now) < 1 {
+          info!("not logging under a minute for \"{}\"", description);
+          continue;
+        }
+        entry.end = Some(now);
+      }
+      entries.push(entry);
+    }
+    storage.save_time_entries(&description, &entries)?;
+  }
+
+  for time in stopped.iter_mut() {
+    time.entry.end = Some(now);
+  }
+  Ok(stopped)
+}
+
+/// Reads keys on another thread so the countdown keeps going while waiting.
+/// There's nothing to read when not run in a terminal.
+fn read_keys(term: &Term) -> Option<Receiver<Key>> {
+  if !term.is_term() {
+    return None;
+  }
+
+  let (tx, rx) = mpsc::channel();
+  let term = term.clone();
+  thread::spawn(move || {
+    // stops after the key that ends the timer so the terminal isn't left
+    // waiting for input; ctrl-c comes through as an error
+    while let Ok(key) = term.read_key() {
+      let is_stop = matches!(key, Key::Char('q') | Key::Escape);
+      if tx.send(key).is_err() || is_stop {
+        break;
+      }
+    }
+  });
+
+  Some(rx)
+}
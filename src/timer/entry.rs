@@ -0,0 +1,202 @@
+use anyhow::{Result, anyhow};
+use chrono::{DateTime, Duration, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::{fmt, sync::LazyLock};
+
+/// `2026-10-17 14:00-15:30`, with the end date given when it's a different
+/// day, no end while the timer is running and a tomato after a pomodoro
+static ENTRY_RE: LazyLock<Regex> = LazyLock::new(|| {
+  Regex::new(
+    r"^(\d{4}-\d{2}-\d{2}) (\d{1,2}:\d{2})\s*-\s*(?:(\d{4}-\d{2}-\d{2}) )?(\d{1,2}:\d{2})?(\s*🍅)?$",
+  )
+  .unwrap()
+});
+
+/// `1h30m`, `2h`, `45m` or `1:30`
+static DURATION_RE: LazyLock<Regex> =
+  LazyLock::new(|| Regex::new(r"^(?:(?:(\d+)h)?\s*(?:(\d+)m(?:in)?)?|(\d+):(\d{2}))$").unwrap());
+
+const DATE_FORMAT: &str = "%Y-%m-%d";
+const TIME_FORMAT: &str = "%H:%M";
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct TimeEntry {
+  pub start: DateTime<Local>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub end: Option<DateTime<Local>>,
+  /// A full work interval of a pomodoro timer
+  #[serde(default, skip_serializing_if = "is_false")]
+  pub pomodoro: bool,
+}
+
+fn is_false(value: &bool) -> bool {
+  !value
+}
+
+/// A time entry along with the task it was tracked against
+#[derive(Clone, Debug, PartialEq)]
+pub struct TaskTime {
+  pub task: String,
+  /// The heading the task is under, in stores that have them
+  pub section: Option<String>,
+  pub entry: TimeEntry,
+}
+
+/// The entry being timed now, the one started last if there are several
+pub fn running(entries: &[TaskTime]) -> Option<&TaskTime> {
+  entries
+    .iter()
+    .filter(|time| time.entry.end.is_none())
+    .max_by_key(|time| time.entry.start)
+}
+
+fn local(date: &str, time: &str) -> Result<DateTime<Local>> {
+  let date = NaiveDate::parse_from_str(date, DATE_FORMAT)?;
+  let time = NaiveTime::parse_from_str(time, TIME_FORMAT)?;
+  to_local(NaiveDateTime::new(date, time))
+}
+
+pub fn to_local(time: NaiveDateTime) -> Result<DateTime<Local>> {
+  Local
+    .from_local_datetime(&time)
+    .earliest()
+    .ok_or_else(|| anyhow!("{} doesn't exist in the local time zone", time))
+}
+
+impl TimeEntry {
+  /// Reads an entry written by `to_string`, e.g. `2026-10-17 14:00-15:30`
+  pub fn parse(text: &str) -> Result<TimeEntry> {
+    let caps = ENTRY_RE
+      .captures(text.trim())
+      .ok_or_else(|| anyhow!("Invalid time entry \"{}\"", text))?;
+
+    let start = local(&caps[1], &caps[2])?;
+    let end = match caps.get(4) {
+      Some(time) => {
+        let date = caps.get(3).map_or(&caps[1], |date| date.as_str());
+        Some(local(date, time.as_str())?)
+      }
+      None => None,
+    };
+
+    Ok(TimeEntry {
+      start,
+      end,
+      pomodoro: caps.get(5).is_some(),
+    })
+  }
+
+  /// How long it's been tracked for, up to `now` while it's running
+  pub fn minutes(&self, now: DateTime<Local>) -> i64 {
+    (self.end.unwrap_or(now) - self.start).num_minutes()
+  }
+
+  /// Whether both were being tracked at the same time, with running entries
+  /// going on until `now`
+  pub fn overlaps(&self, other: &TimeEntry, now: DateTime<Local>) -> bool {
+    self.start < other.end.unwrap_or(now) && other.start < self.end.unwrap_or(now)
+  }
+}
+
+impl fmt::Display for TimeEntry {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(
+      f,
+      "{} {}-",
+      self.start.format(DATE_FORMAT),
+      self.start.format(TIME_FORMAT)
+    )?;
+    match self.end {
+      Some(end) if end.date_naive() == self.start.date_naive() => {
+        write!(f, "{}", end.format(TIME_FORMAT))
+      }
+      Some(end) => write!(f, "{} {}", end.format(DATE_FORMAT), end.format(TIME_FORMAT)),
+      None => Ok(()),
+    }?;
+    if self.pomodoro {
+      write!(f, " 🍅")?;
+    }
+
+    Ok(())
+  }
+}
+
+/// Reads a length of time such as `1h30m`, `45m` or `1:30`
+pub fn parse_duration(text: &str) -> Result<Duration> {
+  let invalid = || anyhow!("Invalid duration \"{}\", e.g. 1h30m or 45m", text);
+  let caps = DURATION_RE.captures(text.trim()).ok_or_else(invalid)?;
+  let number = |i: usize| caps.get(i).map_or(Ok(0), |m| m.as_str().parse::<i64>());
+
+  let (hours, minutes) = if caps.get(3).is_some() {
+    (number(3)?, number(4)?)
+  } else {
+    (number(1)?, number(2)?)
+  };
+  let minutes = hours
+    .checked_mul(60)
+    .and_then(|hours| hours.checked_add(minutes))
+    .filter(|minutes| *minutes > 0)
+    .ok_or_else(invalid)?;
+
+  Duration::try_minutes(minutes).ok_or_else(invalid)
+}
+
+/// Formats a number of minutes as `h:mm`
+pub fn format_minutes(minutes: i64) -> String {
+  format!("{}:{:02}", minutes / 60, minutes % 60)
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn test_parse_and_format() {
+    for text in [
+      "2026-10-17 14:00-15:30",
+      "2026-10-17 23:15-2026-10-18 01:00",
+      "2026-10-17 09:05-",
+      "2026-10-17 09:05-09:30 🍅",
+    ] {
+      assert_eq!(text, TimeEntry::parse(text).unwrap().to_string());
+    }
+
+    let entry = TimeEntry::parse("2026-10-17 9:00 - 10:45").unwrap();
+    assert_eq!(Duration::minutes(105), entry.end.unwrap() - entry.start);
+    assert!(TimeEntry::parse("2026-10-17 14:00").is_err());
+    assert!(TimeEntry::parse("yesterday 14:00-15:00").is_err());
+  }
+
+  #[test]
+  fn test_parse_duration() {
+    assert_eq!(Duration::minutes(90), parse_duration("1h30m").unwrap());
+    assert_eq!(Duration::minutes(120), parse_duration("2h").unwrap());
+    assert_eq!(Duration::minutes(45), parse_duration("45min").unwrap());
+    assert_eq!(Duration::minutes(75), parse_duration("1:15").unwrap());
+    assert!(parse_duration("").is_err());
+    assert!(parse_duration("0m").is_err());
+    assert!(parse_duration("1.5h").is_err());
+    assert!(parse_duration("999999999999999999h").is_err());
+    assert!(parse_duration("99999999999999999:00").is_err());
+  }
+
+  #[test]
+  fn test_overlaps() {
+    let entry = TimeEntry::parse("2026-10-17 14:00-15:30").unwrap();
+    let now = entry.start + Duration::hours(8);
+    let overlaps = |text: &str| entry.overlaps(&TimeEntry::parse(text).unwrap(), now);
+
+    assert!(overlaps("2026-10-17 15:00-16:00"));
+    assert!(overlaps("2026-10-17 14:30-14:45"));
+    assert!(overlaps("2026-10-17 12:00-"));
+    assert!(!overlaps("2026-10-17 15:30-16:00"));
+    assert!(!overlaps("2026-10-17 13:00-14:00"));
+  }
+
+  #[test]
+  fn test_format_minutes() {
+    assert_eq!("0:05", format_minutes(5));
+    assert_eq!("12:30", format_minutes(750));
+  }
+}
@@ -0,0 +1,325 @@
+use anyhow::Result;
+use chrono::{DateTime, Local};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::io::Write;
+
+use crate::tasks::format::csv_field;
+use crate::tasks::{OutputFormat, Task, TaskMeta, TaskStatus};
+use crate::timer::entry::{TaskTime, format_minutes};
+
+/// Minutes logged against each task, by description
+pub fn logged_minutes(entries: &[TaskTime], now: DateTime<Local>) -> HashMap<&str, i64> {
+  let mut minutes = HashMap::new();
+  for time in entries {
+    *minutes.entry(time.task.as_str()).or_default() += time.entry.minutes(now);
+  }
+  minutes
+}
+
+/// Logged time against the estimate, e.g. `1:30/2:00`, or just the logged
+/// time when there's no estimate
+pub fn progress(logged: i64, estimate: Option<i64>) -> String {
+  match estimate {
+    Some(estimate) => format!("{}/{}", format_minutes(logged), format_minutes(estimate)),
+    None => format_minutes(logged),
+  }
+}
+
+/// Logged time as a rounded percentage of the estimate
+pub fn percent(logged: i64, estimate: i64) -> i64 {
+  if estimate <= 0 {
+    return 0;
+  }
+  logged.saturating_mul(100).saturating_add(estimate / 2) / estimate
+}
+
+#[derive(Debug, Default, PartialEq, Serialize)]
+pub struct EstimateRow {
+  pub name: String,
+  pub tasks: usize,
+  pub estimate: i64,
+  pub logged: i64,
+  pub percent: i64,
+}
+
+impl EstimateRow {
+  fn add(&mut self, estimate: i64, logged: i64) {
+    self.tasks += 1;
+    self.estimate = self.estimate.saturating_add(estimate);
+    self.logged = self.logged.saturating_add(logged);
+    self.percent = percent(self.logged, self.estimate);
+  }
+}
+
+/// Tasks that took longer than estimated, and how close the estimates of
+/// completed tasks were for each tag
+#[derive(Debug, PartialEq, Serialize)]
+pub struct EstimateReport {
+  /// Furthest over first
+  pub over: Vec<EstimateRow>,
+  pub tags: Vec<EstimateRow>,
+  /// Each completed task is counted once, even when it has several tags
+  pub total: EstimateRow,
+}
+
+impl EstimateReport {
+  pub fn build(tasks: &[Task], entries: &[TaskTime], now: DateTime<Local>) -> EstimateReport {
+    let logged = logged_minutes(entries, now);
+    let mut over = Vec::new();
+    let mut tags: BTreeMap<String, EstimateRow> = BTreeMap::new();
+    let mut total = EstimateRow {
+      name: String::from("Total"),
+      ..EstimateRow::default()
+    };
+
+    for task in tasks {
+      let meta = TaskMeta::parse(&task.description);
+      let Some(estimate) = meta.estimate else {
+        continue;
+      };
+      let logged = logged.get(task.description.as_str()).copied().unwrap_or(0);
+
+      if logged > estimate && task.status != TaskStatus::Cancelled {
+        let mut row = EstimateRow {
+          name: task.description.clone(),
+          ..EstimateRow::default()
+        };
+        row.add(estimate, logged);
+        over.push(row);
+      }
+
+      // open tasks may not be finished, so only completed ones say how good the estimate was
+      if task.status.is_completed() {
+        total.add(estimate, logged);
+        let names = if meta.tags.is_empty() {
+          vec![String::from("(untagged)")]
+        } else {
+          meta.tags.iter().map(|tag| format!("#{}", tag)).collect()
+        };
+        for name in names {
+          tags
+            .entry(name.clone())
+            .or_insert_with(|| EstimateRow {
+              name,
+              ..EstimateRow::default()
+            })
+            .add(estimate, logged);
+        }
+      }
+    }
+
+    over.sort_by(|a, b| b.percent.cmp(&a.percent).then(a.name.cmp(&b.name)));
+
+    EstimateReport {
+      over,
+      tags: tags.into_values().collect(),
+      total,
+    }
+  }
+
+  pub fn write<W: Write>(&self, out: &mut W, format: OutputFormat) -> Result<()> {
+    match format {
+      OutputFormat::Plain => {
+        if self.over.is_empty() {
+          writeln!(out, "No tasks over their estimate")?;
+        } else {
+          let rows: Vec<&EstimateRow> = self.over.iter().collect();
+          write_plain(out, "Over estimate", &rows, false)?;
+        }
+        writeln!(out)?;
+
+        let mut rows: Vec<&EstimateRow> = self.tags.iter().collect();
+        rows.push(&self.total);
+        write_plain(out, "Tag", &rows, true)?;
+      }
+      OutputFormat::Markdown => {
+        writeln!(out, "## Over estimate\n")?;
+        writeln!(out, "| Task | Estimate | Logged | % |")?;
+        writeln!(out, "| --- | ---: | ---: | ---: |")?;
+        for row in self.over.iter() {
+          writeln!(
+            out,
+            "| {} | {} | {} | {}% |",
+            row.name.replace('|', "\\|"),
+            format_minutes(row.estimate),
+            format_minutes(row.logged),
+            row.percent
+          )?;
+        }
+
+        writeln!(out, "\n## Accuracy by tag\n")?;
+        writeln!(out, "| Tag | Tasks | Estimate | Logged | % |")?;
+        writeln!(out, "| --- | ---: | ---: | ---: | ---: |")?;
+        for row in self.tags.iter() {
+          writeln!(
+            out,
+            "| {} | {} | {} | {} | {}% |",
+            row.name,
+            row.tasks,
+            format_minutes(row.estimate),
+            format_minutes(row.logged),
+            row.percent
+          )?;
+        }
+        writeln!(
+          out,
+          "| **Total** | **{}** | **{}** | **{}** | **{}%** |",
+          self.total.tasks,
+          format_minutes(self.total.estimate),
+          format_minutes(self.total.logged),
+          self.total.percent
+        )?;
+      }
+      OutputFormat::Json => {
+        serde_json::to_writer_pretty(&mut *out, self)?;
+        writeln!(out)?;
+      }
+      OutputFormat::Csv => {
+        writeln!(out, "group,name,tasks,estimate,logged,percent")?;
+        let rows = self
+          .over
+          .iter()
+          .map(|row| ("over", row))
+          .chain(self.tags.iter().map(|row| ("tag", row)))
+          .chain([("total", &self.total)]);
+        for (group, row) in rows {
+          writeln!(
+            out,
+            "{},{},{},{},{},{}",
+            group,
+            csv_field(&row.name),
+            row.tasks,
+            row.estimate,
+            row.logged,
+            row.percent
+          )?;
+        }
+      }
+    }
+
+    Ok(())
+  }
+}
+
+fn write_plain<W: Write>(
+  out: &mut W,
+  heading: &str,
+  rows: &[&EstimateRow],
+  with_tasks: bool,
+) -> Result<()> {
+  let width = rows
+    .iter()
+    .map(|row| row.name.chars().count())
+    .chain([heading.len()])
+    .max()
+    .unwrap_or_default();
+  let tasks = |value: &dyn std::fmt::Display| {
+    if with_tasks {
+      format!("  {:>5}", value)
+    } else {
+      String::new()
+    }
+  };
+
+  writeln!(
+    out,
+    "{:<width$}{}  {:>8}  {:>7}  {:>5}",
+    heading,
+    tasks(&"Tasks"),
+    "Estimate",
+    "Logged",
+    "%"
+  )?;
+  for row in rows {
+    writeln!(
+      out,
+      "{:<width$}{}  {:>8}  {:>7}  {:>4}%",
+      row.name,
+      tasks(&row.tasks),
+      format_minutes(row.estimate),
+      format_minutes(row.logged),
+      row.percent
+    )?;
+  }
+
+  Ok(())
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use crate::timer::TimeEntry;
+
+  fn task(description: &str, status: TaskStatus) -> Task {
+    Task {
+      status,
+      description: String::from(description),
+    }
+  }
+
+  fn time(task: &str, entry: &str) -> TaskTime {
+    TaskTime {
+      task: String::from(task),
+      section: None,
+      entry: TimeEntry::parse(entry).unwrap(),
+    }
+  }
+
+  fn report() -> EstimateReport {
+    let tasks = [
+      task("write spec #api ~1h", TaskStatus::Done),
+      task("fix bug #api #ui ~2h", TaskStatus::Done),
+      task("review ~30m", TaskStatus::Todo),
+      task("no estimate #api", TaskStatus::Done),
+    ];
+    let entries = [
+      time("write spec #api ~1h", "2026-10-01 09:00-10:30"),
+      time("fix bug #api #ui ~2h", "2026-10-02 09:00-10:00"),
+      time("review ~30m", "2026-10-03 09:00-09:45"),
+      time("no estimate #api", "2026-10-03 10:00-11:00"),
+    ];
+    EstimateReport::build(&tasks, &entries, Local::now())
+  }
+
+  #[test]
+  fn test_over_estimate_and_accuracy() {
+    let report = report();
+
+    let over: Vec<(&str, i64)> = report
+      .over
+      .iter()
+      .map(|row| (row.name.as_str(), row.percent))
+      .collect();
+    assert_eq!(
+      vec![("review ~30m", 150), ("write spec #api ~1h", 150)],
+      over
+    );
+
+    let tags: Vec<(&str, usize, i64, i64)> = report
+      .tags
+      .iter()
+      .map(|row| (row.name.as_str(), row.tasks, row.estimate, row.logged))
+      .collect();
+    assert_eq!(vec![("#api", 2, 180, 150), ("#ui", 1, 120, 60)], tags);
+    assert_eq!((2, 83), (report.total.tasks, report.total.percent));
+  }
+
+  #[test]
+  fn test_write_plain() {
+    let mut out = Vec::new();
+    report().write(&mut out, OutputFormat::Plain).unwrap();
+
+    assert_eq!(
+      "Over estimate        Estimate   Logged      %\n\
+       review ~30m              0:30     0:45   150%\n\
+       write spec #api ~1h      1:00     1:30   150%\n\
+       \n\
+       Tag    Tasks  Estimate   Logged      %\n\
+       #api       2      3:00     2:30    83%\n\
+       #ui        1      2:00     1:00    50%\n\
+       Total      2      3:00     2:30    83%\n",
+      String::from_utf8(out).unwrap()
+    );
+  }
+}
@@ -0,0 +1,16 @@
+pub mod controller;
+pub use controller::{TimerController, start_timer, stop_timer};
+
+pub mod entry;
+pub use entry::{TaskTime, TimeEntry};
+
+pub mod estimate;
+pub use estimate::EstimateReport;
+
+pub mod pomodoro;
+
+pub mod report;
+pub use report::{GroupBy, TimeReport};
+
+pub mod status;
+pub use status::{write_running, write_status};
@@ -0,0 +1,95 @@
+use chrono::Duration;
+
+use crate::config::PomodoroConfig;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Phase {
+  Work,
+  ShortBreak,
+  LongBreak,
+}
+
+/// Work and break intervals, with a long break after every few pomodoros
+pub struct Pomodoro<'a> {
+  config: &'a PomodoroConfig,
+  pub phase: Phase,
+  /// Work intervals finished so far
+  pub completed: u32,
+}
+
+impl<'a> Pomodoro<'a> {
+  pub fn new(config: &'a PomodoroConfig) -> Pomodoro<'a> {
+    Pomodoro {
+      config,
+      phase: Phase::Work,
+      completed: 0,
+    }
+  }
+
+  pub fn length(&self) -> Duration {
+    let minutes = match self.phase {
+      Phase::Work => self.config.work,
+      Phase::ShortBreak => self.config.short_break,
+      Phase::LongBreak => self.config.long_break,
+    };
+    Duration::minutes(minutes.into())
+  }
+
+  pub fn label(&self) -> &'static str {
+    match self.phase {
+      Phase::Work => "Work",
+      Phase::ShortBreak => "Break",
+      Phase::LongBreak => "Long break",
+    }
+  }
+
+  /// Moves on to the next interval once the current one has run its length
+  pub fn advance(&mut self) {
+    self.phase = match self.phase {
+      Phase::Work => {
+        self.completed += 1;
+        let after = self.config.long_break_after.max(1);
+        if self.completed.is_multiple_of(after) {
+          Phase::LongBreak
+        } else {
+          Phase::ShortBreak
+        }
+      }
+      Phase::ShortBreak | Phase::LongBreak => Phase::Work,
+    };
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn test_long_break_after_every_few_pomodoros() {
+    let config = PomodoroConfig {
+      long_break_after: 2,
+      ..PomodoroConfig::default()
+    };
+    let mut pomodoro = Pomodoro::new(&config);
+
+    let mut phases = Vec::new();
+    for _ in 0..6 {
+      phases.push(pomodoro.phase);
+      pomodoro.advance();
+    }
+
+    assert_eq!(
+      vec![
+        Phase::Work,
+        Phase::ShortBreak,
+        Phase::Work,
+        Phase::LongBreak,
+        Phase::Work,
+        Phase::ShortBreak,
+      ],
+      phases
+    );
+    assert_eq!(3, pomodoro.completed);
+    assert_eq!(Duration::minutes(25), pomodoro.length());
+  }
+}
@@ -0,0 +1,351 @@
+use anyhow::Result;
+use chrono::{DateTime, Days, Local, NaiveDate, TimeZone};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::Write;
+
+use crate::config::ReportConfig;
+use crate::tasks::format::csv_field;
+use crate::tasks::{OutputFormat, TaskMeta};
+use crate::timer::entry::{TaskTime, format_minutes};
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GroupBy {
+  Task,
+  Tag,
+  Section,
+  Day,
+}
+
+/// Which way each entry is rounded to `round_to` minutes
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Rounding {
+  #[default]
+  Nearest,
+  Up,
+  Down,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct ReportRow {
+  pub name: String,
+  pub minutes: i64,
+}
+
+/// Time tracked between two days, inclusive, grouped into rows
+#[derive(Debug, PartialEq, Serialize)]
+pub struct TimeReport {
+  pub from: NaiveDate,
+  pub to: NaiveDate,
+  pub group_by: GroupBy,
+  pub rows: Vec<ReportRow>,
+  /// Each entry is counted once, even when it's in the rows of several tags
+  pub total: i64,
+}
+
+impl GroupBy {
+  fn heading(&self) -> &'static str {
+    match self {
+      GroupBy::Task => "Task",
+      GroupBy::Tag => "Tag",
+      GroupBy::Section => "Section",
+      GroupBy::Day => "Day",
+    }
+  }
+}
+
+fn start_of(day: NaiveDate) -> DateTime<Local> {
+  let midnight = day.and_hms_opt(0, 0, 0).unwrap();
+  Local
+    .from_local_datetime(&midnight)
+    .earliest()
+    .unwrap_or_else(|| Local.from_utc_datetime(&midnight))
+}
+
+fn round(seconds: i64, config: &ReportConfig) -> i64 {
+  if config.round_to <= 0 {
+    return (seconds + 30) / 60;
+  }
+
+  let step = config.round_to * 60;
+  let steps = match config.rounding {
+    Rounding::Nearest => (seconds + step / 2) / step,
+    Rounding::Up => (seconds + step - 1) / step,
+    Rounding::Down => seconds / step,
+  };
+  steps * config.round_to
+}
+
+impl TimeReport {
+  pub fn build(
+    entries: &[TaskTime],
+    from: NaiveDate,
+    to: NaiveDate,
+    group_by: GroupBy,
+    config: &ReportConfig,
+    now: DateTime<Local>,
+  ) -> TimeReport {
+    let mut minutes: HashMap<String, i64> = HashMap::new();
+    let mut total = 0;
+
+    for time in entries {
+      let end = time.entry.end.unwrap_or(now);
+
+      // split by day so each day can be rounded and grouped on its own
+      let mut day = from.max(time.entry.start.date_naive());
+      while day <= to && start_of(day) < end {
+        let next = day.checked_add_days(Days::new(1)).unwrap();
+        let start = time.entry.start.max(start_of(day));
+        let seconds = (end.min(start_of(next)) - start).num_seconds();
+
+        if seconds > 0 {
+          let rounded = round(seconds, config);
+          total += rounded;
+          for name in group_names(time, group_by, day) {
+            *minutes.entry(name).or_default() += rounded;
+          }
+        }
+        day = next;
+      }
+    }
+
+    let mut rows: Vec<ReportRow> = minutes
+      .into_iter()
+      .map(|(name, minutes)| ReportRow { name, minutes })
+      .collect();
+    match group_by {
+      GroupBy::Day => rows.sort_by(|a, b| a.name.cmp(&b.name)),
+      _ => rows.sort_by(|a, b| b.minutes.cmp(&a.minutes).then(a.name.cmp(&b.name))),
+    }
+
+    TimeReport {
+      from,
+      to,
+      group_by,
+      rows,
+      total,
+    }
+  }
+
+  pub fn write<W: Write>(&self, out: &mut W, format: OutputFormat) -> Result<()> {
+    let hours = |minutes: i64| format!("{:.2}", minutes as f64 / 60.0);
+
+    match format {
+      OutputFormat::Plain => {
+        let width = self
+          .rows
+          .iter()
+          .map(|row| row.name.chars().count())
+          .chain([self.group_by.heading().len()])
+          .max()
+          .unwrap_or_default();
+
+        writeln!(out, "{:<width$}  {:>7}", self.group_by.heading(), "Time")?;
+        for row in self.rows.iter() {
+          writeln!(
+            out,
+            "{:<width$}  {:>7}",
+            row.name,
+            format_minutes(row.minutes)
+          )?;
+        }
+        writeln!(
+          out,
+          "{:<width$}  {:>7}",
+          "Total",
+          format_minutes(self.total)
+        )?;
+      }
+      OutputFormat::Markdown => {
+        writeln!(out, "| {} | Time | Hours |", self.group_by.heading())?;
+        writeln!(out, "| --- | ---: | ---: |")?;
+        for row in self.rows.iter() {
+          writeln!(
+            out,
+            "| {} | {} | {} |",
+            row.name.replace('|', "\\|"),
+            format_minutes(row.minutes),
+            hours(row.minutes)
+          )?;
+        }
+        writeln!(
+          out,
+          "| **Total** | **{}** | **{}** |",
+          format_minutes(self.total),
+          hours(self.total)
+        )?;
+      }
+      OutputFormat::Json => {
+        serde_json::to_writer_pretty(&mut *out, self)?;
+        writeln!(out)?;
+      }
+      OutputFormat::Csv => {
+        writeln!(
+          out,
+          "{},minutes,hours",
+          self.group_by.heading().to_lowercase()
+        )?;
+        for row in self.rows.iter() {
+          writeln!(
+            out,
+            "{},{},{}",
+            csv_field(&row.name),
+            row.minutes,
+            hours(row.minutes)
+          )?;
+        }
+        writeln!(out, "Total,{},{}", self.total, hours(self.total))?;
+      }
+    }
+
+    Ok(())
+  }
+}
+
+fn group_names(time: &TaskTime, group_by: GroupBy, day: NaiveDate) -> Vec<String> {
+  match group_by {
+    GroupBy::Task => vec![time.task.clone()],
+    GroupBy::Tag => {
+      let tags = TaskMeta::parse(&time.task).tags;
+      if tags.is_empty() {
+        vec![String::from("(untagged)")]
+      } else {
+        tags.into_iter().map(|tag| format!("#{}", tag)).collect()
+      }
+    }
+    GroupBy::Section => vec![
+      time
+        .section
+        .clone()
+        .unwrap_or_else(|| String::from("(no section)")),
+    ],
+    GroupBy::Day => vec![day.to_string()],
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use crate::timer::TimeEntry;
+
+  fn time(task: &str, section: Option<&str>, entry: &str) -> TaskTime {
+    TaskTime {
+      task: String::from(task),
+      section: section.map(String::from),
+      entry: TimeEntry::parse(entry).unwrap(),
+    }
+  }
+
+  fn entries() -> Vec<TaskTime> {
+    vec![
+      time("write spec #api", Some("Work"), "2026-10-01 09:00-10:20"),
+      time(
+        "write spec #api",
+        Some("Work"),
+        "2026-10-02 23:00-2026-10-03 01:00",
+      ),
+      time("fix bug #api #ui", Some("Work"), "2026-10-03 14:00-14:07"),
+      time("lunch", None, "2026-09-30 12:00-13:00"),
+    ]
+  }
+
+  fn date(day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(2026, 10, day).unwrap()
+  }
+
+  fn build(group_by: GroupBy, config: &ReportConfig) -> TimeReport {
+    TimeReport::build(
+      &entries(),
+      date(1),
+      date(31),
+      group_by,
+      config,
+      Local::now(),
+    )
+  }
+
+  fn rows(report: &TimeReport) -> Vec<(&str, i64)> {
+    report
+      .rows
+      .iter()
+      .map(|row| (row.name.as_str(), row.minutes))
+      .collect()
+  }
+
+  #[test]
+  fn test_group_by() {
+    let config = ReportConfig::default();
+
+    let report = build(GroupBy::Task, &config);
+    assert_eq!(
+      vec![("write spec #api", 200), ("fix bug #api #ui", 7)],
+      rows(&report)
+    );
+    assert_eq!(207, report.total);
+
+    let report = build(GroupBy::Tag, &config);
+    assert_eq!(vec![("#api", 207), ("#ui", 7)], rows(&report));
+    assert_eq!(207, report.total);
+
+    let report = build(GroupBy::Day, &config);
+    assert_eq!(
+      vec![("2026-10-01", 80), ("2026-10-02", 60), ("2026-10-03", 67)],
+      rows(&report)
+    );
+
+    let report = build(GroupBy::Section, &config);
+    assert_eq!(vec![("Work", 207)], rows(&report));
+  }
+
+  #[test]
+  fn test_rounding() {
+    let mut config = ReportConfig {
+      round_to: 15,
+      rounding: Rounding::Nearest,
+    };
+    assert_eq!(75 + 60 + 60, build(GroupBy::Day, &config).total);
+
+    config.rounding = Rounding::Up;
+    assert_eq!(90 + 60 + 60 + 15, build(GroupBy::Day, &config).total);
+
+    config.rounding = Rounding::Down;
+    assert_eq!(75 + 60 + 60, build(GroupBy::Day, &config).total);
+  }
+
+  #[test]
+  fn test_write_formats() {
+    let report = TimeReport::build(
+      &entries()[..1],
+      date(1),
+      date(1),
+      GroupBy::Task,
+      &ReportConfig::default(),
+      Local::now(),
+    );
+    let render = |format: OutputFormat| {
+      let mut out = Vec::new();
+      report.write(&mut out, format).unwrap();
+      String::from_utf8(out).unwrap()
+    };
+
+    assert_eq!(
+      "Task                Time\nwrite spec #api     1:20\nTotal               1:20\n",
+      render(OutputFormat::Plain)
+    );
+    assert_eq!(
+      "| Task | Time | Hours |\n| --- | ---: | ---: |\n\
+       | write spec #api | 1:20 | 1.33 |\n| **Total** | **1:20** | **1.33** |\n",
+      render(OutputFormat::Markdown)
+    );
+    assert_eq!(
+      "task,minutes,hours\nwrite spec #api,80,1.33\nTotal,80,1.33\n",
+      render(OutputFormat::Csv)
+    );
+
+    let value: serde_json::Value = serde_json::from_str(&render(OutputFormat::Json)).unwrap();
+    assert_eq!(value["rows"][0]["minutes"], 80);
+    assert_eq!(value["group_by"], "task");
+  }
+}
@@ -0,0 +1,116 @@
+use anyhow::Result;
+use chrono::{DateTime, Local};
+use std::io::Write;
+
+use crate::tasks::{GetTasksFilterOption, TaskList, TaskMeta};
+use crate::timer::entry::{TaskTime, format_minutes, running};
+use crate::timer::estimate::{logged_minutes, percent, progress};
+
+/// Writes the time logged against the estimate of each open task that has
+/// either, or of just the task `selector` picks
+pub fn write_status<W: Write>(
+  out: &mut W,
+  tasklist: &TaskList,
+  entries: &[TaskTime],
+  selector: Option<&str>,
+  now: DateTime<Local>,
+) -> Result<()> {
+  let logged = logged_minutes(entries, now);
+  let tasks = match selector {
+    Some(selector) => vec![tasklist.select(selector)?],
+    None => tasklist
+      .get_tasks(&GetTasksFilterOption::Incomplete)
+      .into_iter()
+      .filter(|task| {
+        logged.contains_key(task.description.as_str())
+          || TaskMeta::parse(&task.description).estimate.is_some()
+      })
+      .collect(),
+  };
+  if tasks.is_empty() {
+    writeln!(out, "No open tasks with time logged or estimated")?;
+    return Ok(());
+  }
+
+  let width = tasks
+    .iter()
+    .map(|task| task.description.chars().count())
+    .max()
+    .unwrap_or_default();
+  for task in tasks {
+    let estimate = TaskMeta::parse(&task.description).estimate;
+    let minutes = logged.get(task.description.as_str()).copied().unwrap_or(0);
+    let percent = match estimate {
+      Some(estimate) => format!("  {:>4}%", percent(minutes, estimate)),
+      None => String::new(),
+    };
+    writeln!(
+      out,
+      "{:<width$}  {:>11}{}",
+      task.description,
+      progress(minutes, estimate),
+      percent
+    )?;
+  }
+
+  Ok(())
+}
+
+/// Writes the task being timed and for how long on one line, e.g. for a
+/// shell prompt, or nothing when no timer is running
+pub fn write_running<W: Write>(
+  out: &mut W,
+  entries: &[TaskTime],
+  now: DateTime<Local>,
+) -> Result<()> {
+  let Some(time) = running(entries) else {
+    return Ok(());
+  };
+
+  write!(
+    out,
+    "⏱ {} {}",
+    time.task,
+    format_minutes(time.entry.minutes(now))
+  )?;
+  if let Some(estimate) = TaskMeta::parse(&time.task).estimate {
+    let logged = logged_minutes(entries, now)[time.task.as_str()];
+    write!(out, " ({})", progress(logged, Some(estimate)))?;
+  }
+  writeln!(out)?;
+
+  Ok(())
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use crate::timer::TimeEntry;
+
+  fn time(task: &str, entry: &str) -> TaskTime {
+    TaskTime {
+      task: String::from(task),
+      section: None,
+      entry: TimeEntry::parse(entry).unwrap(),
+    }
+  }
+
+  #[test]
+  fn test_write_running() {
+    let mut entries = vec![
+      time("write spec ~2h", "2026-10-17 09:00-10:00"),
+      time("lunch", "2026-10-18 12:00-13:00"),
+    ];
+    let now = entries[1].entry.end.unwrap() + chrono::Duration::minutes(25);
+    let render = |entries: &[TaskTime]| {
+      let mut out = Vec::new();
+      write_running(&mut out, entries, now).unwrap();
+      String::from_utf8(out).unwrap()
+    };
+
+    assert_eq!("", render(&entries));
+
+    entries.push(time("write spec ~2h", "2026-10-18 13:00-"));
+    assert_eq!("⏱ write spec ~2h 0:25 (1:25/2:00)\n", render(&entries));
+  }
+}
@@ -0,0 +1,395 @@
+use anyhow::Result;
+use chrono::{Local, NaiveDate};
+use log::{debug, info};
+use std::fs;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+
+use crate::storage;
+use crate::tasks::{Task, TaskChange, TaskList, TaskListPersist, TaskMeta, TaskStatus};
+
+const DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// A task in todo.txt format, e.g.
+/// `x 2026-10-18 2026-10-01 Call Bob +sales @phone due:2026-10-19 pri:A`
+#[derive(Clone, Debug, PartialEq)]
+struct Entry {
+  /// The line as read, written back untouched unless the task changes
+  line: String,
+  /// Anything but todo and done is kept in a `status:` extra, e.g. `status:in-progress`
+  status: TaskStatus,
+  completed: Option<NaiveDate>,
+  created: Option<NaiveDate>,
+  /// `A` to `Z`, only `A` to `D` have a matching `p1` to `p4`
+  priority: Option<char>,
+  /// The text after the dates and priority, with `+project` read as `#project`
+  /// and the priority put first as `p1` to `p4`
+  description: String,
+  /// The words of the text as read, written back verbatim
+  text: Vec<String>,
+}
+
+fn parse_date(word: Option<&&str>) -> Option<NaiveDate> {
+  word.and_then(|word| NaiveDate::parse_from_str(word, DATE_FORMAT).ok())
+}
+
+fn parse_priority(word: &str) -> Option<char> {
+  let letter = word.strip_prefix('(')?.strip_suffix(')')?;
+  let mut chars = letter.chars();
+  match (chars.next(), chars.next()) {
+    (Some(letter), None) if letter.is_ascii_uppercase() => Some(letter),
+    _ => None,
+  }
+}
+
+impl Entry {
+  fn parse(line: &str) -> Entry {
+    let mut words: Vec<&str> = line.split_whitespace().collect();
+    let mut entry = Entry {
+      line: line.to_string(),
+      status: TaskStatus::Todo,
+      completed: None,
+      created: None,
+      priority: None,
+      description: String::new(),
+      text: Vec::new(),
+    };
+
+    let mut i = 0;
+    if words.first() == Some(&"x") {
+      entry.status = TaskStatus::Done;
+      i += 1;
+      if let Some(date) = parse_date(words.get(i)) {
+        entry.completed = Some(date);
+        i += 1;
+      }
+    } else if let Some(priority) = words.first().and_then(|word| parse_priority(word)) {
+      entry.priority = Some(priority);
+      i += 1;
+    }
+    if let Some(date) = parse_date(words.get(i)) {
+      entry.created = Some(date);
+      i += 1;
+    }
+
+    if let Some(pos) = words.iter().position(|word| word.starts_with("status:"))
+      && let Some(status) = TaskStatus::from_name(&words[pos][7..])
+    {
+      entry.status = status;
+      words.remove(pos);
+    }
+
+    // completing a task moves its priority into a `pri:` extra
+    if entry.status.is_closed()
+      && let Some(pos) = words.iter().position(|word| word.starts_with("pri:"))
+      && let Some(priority) = parse_priority(&format!("({})", &words[pos][4..]))
+    {
+      entry.priority = Some(priority);
+      words.remove(pos);
+    }
+
+    entry.text = words[i..].iter().map(|word| word.to_string()).collect();
+
+    // ahead of the text so a `p2` in it doesn't win over the priority
+    let mut description = Vec::new();
+    if let Some(priority @ 'A'..='D') = entry.priority {
+      description.push(format!("p{}", priority as u8 - b'A' + 1));
+    }
+    description.extend(entry.text.iter().map(|word| match word.strip_prefix('+') {
+      Some(project) if !project.is_empty() => format!("#{}", project),
+      _ => word.clone(),
+    }));
+    entry.description = description.join(" ");
+
+    entry
+  }
+
+  fn new(task: Task) -> Entry {
+    let today = Local::now().date_naive();
+    let mut entry = Entry {
+      line: String::new(),
+      status: TaskStatus::Todo,
+      completed: None,
+      created: Some(today),
+      priority: None,
+      description: String::new(),
+      text: Vec::new(),
+    };
+    entry.update(task);
+    entry
+  }
+
+  fn update(&mut self, task: Task) {
+    if task.status.is_closed() != self.status.is_closed() {
+      self.completed = task.status.is_closed().then(|| Local::now().date_naive());
+    }
+    self.status = task.status;
+
+    self.description = task.description;
+    // priorities below D can't be written inline so they're kept as they were
+    self.priority = match self.text_words().0 {
+      Some(priority) => Some((b'A' + priority - 1) as char),
+      None => self.priority.filter(|priority| *priority > 'D'),
+    };
+    self.line = self.format();
+  }
+
+  /// The priority and the words to write for the description. Words of the
+  /// text as read are kept verbatim, only the ones added to it are mapped.
+  fn text_words(&self) -> (Option<u8>, Vec<String>) {
+    let mut text: Vec<&str> = self.text.iter().map(String::as_str).collect();
+    let mut priority = None;
+    let mut words = Vec::new();
+    for word in self.description.split_whitespace() {
+      if let Some(pos) = text.iter().position(|text| *text == word) {
+        text.remove(pos);
+        words.push(word.to_string());
+        continue;
+      }
+      match (TaskMeta::parse(word).priority, word.strip_prefix('#')) {
+        (Some(p), _) if priority.is_none() => priority = Some(p),
+        (_, Some(tag)) if !tag.is_empty() => words.push(format!("+{}", tag)),
+        _ => words.push(word.to_string()),
+      }
+    }
+    (priority, words)
+  }
+
+  fn format(&self) -> String {
+    let mut words = Vec::new();
+
+    let is_closed = self.status.is_closed();
+    if is_closed {
+      words.push(String::from("x"));
+      if let Some(completed) = self.completed {
+        words.push(completed.format(DATE_FORMAT).to_string());
+      }
+    } else if let Some(priority) = self.priority {
+      words.push(format!("({})", priority));
+    }
+    // the spec only allows a creation date after a completion date
+    if let Some(created) = self.created
+      && (!is_closed || self.completed.is_some())
+    {
+      words.push(created.format(DATE_FORMAT).to_string());
+    }
+
+    words.extend(self.text_words().1);
+
+    if is_closed && let Some(priority) = self.priority {
+      words.push(format!("pri:{}", priority));
+    }
+    if !matches!(self.status, TaskStatus::Todo | TaskStatus::Done) {
+      words.push(format!("status:{}", self.status));
+    }
+
+    words.join(" ")
+  }
+}
+
+pub struct File<'a> {
+  pub path: &'a str,
+  entries: Vec<Entry>,
+  backup: bool,
+  lock: Option<fs::File>,
+}
+
+impl<'a> File<'a> {
+  pub fn from(path: &'a str) -> File<'a> {
+    File {
+      path,
+      entries: Vec::new(),
+      backup: false,
+      lock: None,
+    }
+  }
+
+  /// Keep the previous version of the file as `<path>.bak` on every save
+  pub fn with_backup(mut self, backup: bool) -> File<'a> {
+    self.backup = backup;
+    self
+  }
+
+  pub fn read_file(&mut self) -> Result<()> {
+    let contents = match fs::read_to_string(self.path) {
+      Ok(contents) => contents,
+      // created on the first save
+      Err(err) if err.kind() == ErrorKind::NotFound => String::new(),
+      Err(err) => return Err(err.into()),
+    };
+
+    self.entries = contents
+      .lines()
+      .filter(|line| !line.trim().is_empty())
+      .map(Entry::parse)
+      .collect();
+
+    Ok(())
+  }
+
+  pub fn write_file(&self) -> Result<()> {
+    let mut contents = String::new();
+    for entry in self.entries.iter() {
+      contents.push_str(&entry.line);
+      contents.push('\n');
+    }
+
+    storage::write_atomic(self.path, &contents, self.backup)
+  }
+
+  fn apply_changes(&mut self, changes: Vec<TaskChange>) {
+    for change in changes {
+      debug!("applying {:?}", change);
+      match change {
+        TaskChange::Added(task) => self.entries.push(Entry::new(task)),
+        TaskChange::Updated { original, task } => {
+          if let Some(entry) = self.entries.iter_mut().find(|e| e.description == original) {
+            entry.update(task);
+          }
+        }
+        TaskChange::Deleted(original) => self.entries.retain(|e| e.description != original),
+      }
+    }
+  }
+}
+
+impl<'a> TaskListPersist for File<'a> {
+  fn lock(&mut self) -> Result<()> {
+    self.lock = Some(storage::lock(self.path)?);
+    Ok(())
+  }
+
+  fn unlock(&mut self) {
+    self.lock = None;
+  }
+
+  fn watch_path(&self) -> Option<PathBuf> {
+    Some(PathBuf::from(self.path))
+  }
+
+  fn load_tasklist(&mut self) -> Result<TaskList> {
+    info!("loading tasks from todo.txt file");
+    self.read_file()?;
+
+    let tasks = self
+      .entries
+      .iter()
+      .map(|entry| Task {
+        description: entry.description.clone(),
+        status: entry.status,
+      })
+      .collect();
+
+    Ok(TaskList::from(tasks))
+  }
+
+  fn save_tasklist(&mut self, tasklist: &mut TaskList) -> Result<()> {
+    self.apply_changes(tasklist.changes());
+    self.write_file()
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use crate::tasks::{GetTasksFilterOption, TaskUpdateAction};
+
+  #[test]
+  fn test_parse_entry() {
+    let entry = Entry::parse("(A) 2026-10-01 Call Bob +sales @phone due:2026-10-19 id:42");
+    assert_eq!(TaskStatus::Todo, entry.status);
+    assert_eq!(Some('A'), entry.priority);
+    assert_eq!(NaiveDate::from_ymd_opt(2026, 10, 1), entry.created);
+    assert_eq!(
+      "p1 Call Bob #sales @phone due:2026-10-19 id:42",
+      entry.description
+    );
+
+    let meta = TaskMeta::parse(&entry.description);
+    assert_eq!(vec!["sales"], meta.tags);
+    assert_eq!(Some(String::from("phone")), meta.context);
+    assert_eq!(Some(1), meta.priority);
+
+    let entry = Entry::parse("x 2026-10-18 2026-10-01 Pay rent pri:B");
+    assert_eq!(TaskStatus::Done, entry.status);
+    assert_eq!(NaiveDate::from_ymd_opt(2026, 10, 18), entry.completed);
+    assert_eq!(NaiveDate::from_ymd_opt(2026, 10, 1), entry.created);
+    assert_eq!(Some('B'), entry.priority);
+    assert_eq!("p2 Pay rent", entry.description);
+    assert_eq!("x 2026-10-18 2026-10-01 Pay rent pri:B", entry.format());
+
+    let entry = Entry::parse("x 2026-10-18 Skip the party status:cancelled");
+    assert_eq!(TaskStatus::Cancelled, entry.status);
+    assert_eq!("Skip the party", entry.description);
+    assert_eq!(
+      "x 2026-10-18 Skip the party status:cancelled",
+      entry.format()
+    );
+
+    let entry = Entry::parse("(F) Someday +garden");
+    assert_eq!("Someday #garden", entry.description);
+    assert_eq!("(F) Someday +garden", entry.format());
+  }
+
+  #[test]
+  fn test_update_keeps_text() {
+    let mut entry = Entry::parse("Fix bug #42 in parser +code");
+    entry.update(Task {
+      description: entry.description.clone(),
+      status: TaskStatus::InProgress,
+    });
+    assert_eq!("Fix bug #42 in parser +code status:in-progress", entry.line);
+
+    let mut entry = Entry::parse("(A) Deploy p2 cluster");
+    assert_eq!(Some(1), TaskMeta::parse(&entry.description).priority);
+    entry.update(Task {
+      description: entry.description.clone(),
+      status: TaskStatus::Done,
+    });
+    assert_eq!(Some('A'), entry.priority);
+    assert!(entry.line.ends_with(" Deploy p2 cluster pri:A"));
+
+    let mut entry = Entry::parse("Deploy p2 cluster");
+    entry.update(Task {
+      description: entry.description.replace("cluster", "cluster #ops p1"),
+      status: TaskStatus::Todo,
+    });
+    assert_eq!("(A) Deploy p2 cluster +ops", entry.line);
+  }
+
+  #[test]
+  fn test_save_and_load() {
+    let dir = std::env::temp_dir().join(format!("taskmaster-todotxt-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("todo.txt").to_string_lossy().to_string();
+    let contents = "(B) 2026-10-01  Water   plants +garden  \n\
+                    2026-10-02 Fix bike rec:weekly color:red\n\
+                    x 2026-10-03 Old task\n";
+    fs::write(&path, contents).unwrap();
+
+    let mut file = File::from(&path);
+    let mut tasklist = file.load_tasklist().unwrap();
+    tasklist.update_task(TaskUpdateAction::Toggle, "Fix bike rec:weekly color:red");
+    tasklist.update_task(TaskUpdateAction::Delete, "Old task");
+    tasklist
+      .add_task(String::from("Buy milk p1 #errands"))
+      .unwrap();
+    file.save_tasklist(&mut tasklist).unwrap();
+
+    let today = Local::now().date_naive().format(DATE_FORMAT);
+    assert_eq!(
+      format!(
+        "(B) 2026-10-01  Water   plants +garden  \n\
+         x {today} 2026-10-02 Fix bike rec:weekly color:red\n\
+         (A) {today} Buy milk +errands\n"
+      ),
+      fs::read_to_string(&path).unwrap()
+    );
+
+    let mut file = File::from(&path);
+    let tasklist = file.load_tasklist().unwrap();
+    assert_eq!(3, tasklist.get_tasks(&GetTasksFilterOption::All).len());
+
+    fs::remove_dir_all(&dir).unwrap();
+  }
+}